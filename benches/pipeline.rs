@@ -0,0 +1,98 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use opencv::core::{copy_make_border, Mat, Point2f, Scalar, Vector, BORDER_CONSTANT};
+use opencv::imgcodecs;
+use opencv::prelude::*;
+use barcode_detector::service::barcode::{create_detector, enhance_vertical_lines_with_scaling, extract_and_expand};
+use barcode_detector::service::encoder::{encode, render_mat, RenderOptions, Symbology};
+use barcode_detector::service::image::read_gray_mat_from_base64;
+use barcode_detector::service::options::DecodeOptions;
+
+// 常见的拍摄分辨率：VGA、1.2MP、5MP
+const SIZES: [(i32, i32); 3] = [(640, 480), (1280, 960), (2592, 1944)];
+
+struct Scene {
+    image: Mat,
+    // 条码区域（不含静区）的四个角点
+    points: Vec<Point2f>,
+    module: f32,
+}
+
+// 白底图片中央放一个约占三分之一宽度的 EAN-13
+fn scene(width: i32, height: i32) -> Scene {
+    let module = (width / 3 / 95).max(1);
+    let options = RenderOptions {
+        module_size: module as u32,
+        bar_height: (height / 4) as u32,
+        ..RenderOptions::default()
+    };
+    let encoded = encode(Symbology::Ean13, "6901234567892", &options).unwrap();
+    let code = render_mat(&encoded, &options).unwrap();
+    let (left, top) = ((width - code.cols()) / 2, (height - code.rows()) / 2);
+    let mut image = Mat::default();
+    copy_make_border(&code, &mut image, top, height - code.rows() - top, left, width - code.cols() - left, BORDER_CONSTANT, Scalar::all(255.0)).unwrap();
+
+    let quiet = 10 * module;
+    let (x0, y0) = ((left + quiet) as f32, (top + quiet) as f32);
+    let (x1, y1) = (x0 + (95 * module) as f32, y0 + options.bar_height as f32);
+    Scene {
+        image,
+        points: vec![Point2f::new(x0, y0), Point2f::new(x1, y0), Point2f::new(x1, y1), Point2f::new(x0, y1)],
+        module: module as f32,
+    }
+}
+
+fn bench_stages(c: &mut Criterion) {
+    let options = DecodeOptions::default();
+    let detector = create_detector(&options.detector).unwrap();
+    let mut group = c.benchmark_group("pipeline");
+    group.sample_size(20);
+    for (width, height) in SIZES {
+        let id = format!("{}x{}", width, height);
+        let scene = scene(width, height);
+
+        let mut png = Vector::<u8>::new();
+        imgcodecs::imencode(".png", &scene.image, &mut png, &Vector::new()).unwrap();
+        let data_uri = format!("data:image/png;base64,{}", STANDARD.encode(png.as_slice()));
+        group.bench_with_input(BenchmarkId::new("load", &id), &data_uri, |b, data| {
+            b.iter(|| read_gray_mat_from_base64(data).unwrap())
+        });
+
+        group.bench_with_input(BenchmarkId::new("detect_multi", &id), &scene.image, |b, image| {
+            b.iter(|| {
+                let mut points = Vector::<Point2f>::new();
+                detector.detect_multi(image, &mut points).unwrap()
+            })
+        });
+
+        // 与 ExpandOptions 默认值一致：左右各 10 个模块，上下各扩展条高的 50%
+        let (horizontal, vertical) = (scene.module * 10.0, (scene.points[3].y - scene.points[0].y) * 0.5);
+        group.bench_with_input(BenchmarkId::new("extract_and_expand", &id), &scene, |b, scene| {
+            b.iter(|| extract_and_expand(&scene.image, &scene.points, horizontal, vertical).unwrap())
+        });
+
+        let region = extract_and_expand(&scene.image, &scene.points, horizontal, vertical).unwrap();
+        group.bench_with_input(BenchmarkId::new("enhance_vertical_lines_with_scaling", &id), &region, |b, region| {
+            b.iter(|| enhance_vertical_lines_with_scaling(region, Some(scene.module), &options.scale, None).unwrap())
+        });
+
+        let enhanced = enhance_vertical_lines_with_scaling(&region, Some(scene.module), &options.scale, None).unwrap();
+        let corners = Vector::<Point2f>::from_slice(&[
+            Point2f::new(0.0, 0.0),
+            Point2f::new(enhanced.cols() as f32, 0.0),
+            Point2f::new(enhanced.cols() as f32, enhanced.rows() as f32),
+            Point2f::new(0.0, enhanced.rows() as f32),
+        ]);
+        group.bench_with_input(BenchmarkId::new("decode", &id), &enhanced, |b, enhanced| {
+            b.iter(|| {
+                let mut straight_code = Mat::default();
+                detector.decode(enhanced, &corners, &mut straight_code).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_stages);
+criterion_main!(benches);
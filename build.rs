@@ -13,7 +13,7 @@ fn get_windows_dlls() -> (String, Vec<String>) {
 }
 
 fn get_opencv_dlls() -> (String, Vec<String>) {
-    let dlls = vec![
+    let mut dlls = vec![
         "opencv_core4.dll".to_string(),
         "opencv_calib3d4.dll".to_string(),
         "opencv_dnn4.dll".to_string(),
@@ -23,11 +23,14 @@ fn get_opencv_dlls() -> (String, Vec<String>) {
         "opencv_imgproc4.dll".to_string(),
         "opencv_objdetect4.dll".to_string(),
     ];
+    if env::var("CARGO_FEATURE_SUPERRES").is_ok() {
+        dlls.push("opencv_dnn_superres4.dll".to_string());
+    }
     ("E:/packages/vcpkg/installed/x64-windows/bin".to_string(), dlls)
 }
 
 fn get_opencv_debug_dlls() -> (String, Vec<String>) {
-    let dlls = vec![
+    let mut dlls = vec![
         "opencv_core4d.dll".to_string(),
         "opencv_calib3d4d.dll".to_string(),
         "opencv_dnn4d.dll".to_string(),
@@ -37,6 +40,9 @@ fn get_opencv_debug_dlls() -> (String, Vec<String>) {
         "opencv_imgproc4d.dll".to_string(),
         "opencv_objdetect4d.dll".to_string(),
     ];
+    if env::var("CARGO_FEATURE_SUPERRES").is_ok() {
+        dlls.push("opencv_dnn_superres4d.dll".to_string());
+    }
     ("E:/packages/vcpkg/packages/opencv4_x64-windows/debug/bin".to_string(), dlls)
 }
 
@@ -160,6 +166,9 @@ fn main() {
     println!("cargo:rustc-link-lib=dylib=opencv_dnn4");
     println!("cargo:rustc-link-lib=dylib=opencv_imgcodecs4");
     println!("cargo:rustc-link-lib=dylib=opencv_objdetect4");
+    if env::var("CARGO_FEATURE_SUPERRES").is_ok() {
+        println!("cargo:rustc-link-lib=dylib=opencv_dnn_superres4");
+    }
 
     // 设置 OpenCV 头文件的路径（对于 C++ 绑定）
     println!("cargo:include=E:/packages/vcpkg/installed/x64-windows/include");
@@ -0,0 +1,15 @@
+#![no_main]
+
+// AAMVA 驾照 PDF417 数据解析：头部中的偏移、长度等字段均不可信，不应 panic
+
+use barcode_detector::service::aamva::parse_aamva;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    // 以 @ 开头才会进入完整的头部解析
+    let text = format!("@{}", data);
+    if let Ok(license) = parse_aamva(&text) {
+        let json = serde_json::to_string(&license).unwrap();
+        assert!(json.len() <= 4096 + text.len() * 16, "output too large: {} bytes for {} input bytes", json.len(), text.len());
+    }
+});
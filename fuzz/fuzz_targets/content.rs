@@ -0,0 +1,13 @@
+#![no_main]
+
+// 二维码内容分类解析（WiFi、vCard/MECARD、日历事件、支付码等）：不应 panic，输出大小与输入成正比
+
+use barcode_detector::service::content::parse_content;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    if let Some(content) = parse_content(data) {
+        let json = serde_json::to_string(&content).unwrap();
+        assert!(json.len() <= 4096 + data.len() * 16, "output too large: {} bytes for {} input bytes", json.len(), data.len());
+    }
+});
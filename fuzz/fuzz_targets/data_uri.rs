@@ -0,0 +1,23 @@
+#![no_main]
+
+// base64 / data URI 载入：任意字符串都不应 panic，解码出的图片不超过 ImageLimits
+// cargo fuzz run data_uri
+
+use barcode_detector::service::image::read_gray_mat_from_base64_with_limits;
+use barcode_detector::service::limits::ImageLimits;
+use libfuzzer_sys::fuzz_target;
+use opencv::prelude::*;
+
+const LIMITS: ImageLimits = ImageLimits {
+    max_input_bytes: 1024 * 1024,
+    max_width: 2048,
+    max_height: 2048,
+    max_pixels: 1024 * 1024,
+};
+
+fuzz_target!(|data: &str| {
+    if let Ok(image) = read_gray_mat_from_base64_with_limits(data, &LIMITS) {
+        let pixels = image.rows() as u64 * image.cols() as u64;
+        assert!(pixels <= LIMITS.max_pixels, "decoded {}x{} exceeds the pixel limit", image.cols(), image.rows());
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+// GS1 元素串与 Digital Link 解析：不应 panic，输出大小与输入成正比
+
+use barcode_detector::service::gs1::{parse_digital_link, parse_gs1};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    for parsed in [parse_gs1(data), parse_digital_link(data)].into_iter().flatten() {
+        let json = serde_json::to_string(&parsed).unwrap();
+        // 每个元素带有固定的标题等字段，按每个输入字节最多产生 64 字节输出估算
+        assert!(json.len() <= 4096 + data.len() * 64, "output too large: {} bytes for {} input bytes", json.len(), data.len());
+    }
+});
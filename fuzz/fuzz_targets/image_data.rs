@@ -0,0 +1,13 @@
+#![no_main]
+
+// data URI 与 base64 解析（datauri::decode_image_data）：不应 panic，
+// 解码结果不超过输入长度（base64 为 3/4，百分号编码不会变长）
+
+use barcode_detector::service::datauri::decode_image_data;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    if let Ok(bytes) = decode_image_data(data) {
+        assert!(bytes.len() <= data.len(), "decoded {} bytes from {} input bytes", bytes.len(), data.len());
+    }
+});
@@ -0,0 +1,35 @@
+#![no_main]
+
+// 图像解码与识别：任意字节先按 ImageLimits 检查头部声明的尺寸再解码，成功时再走一遍完整识别流程。
+// 限制在解码前生效，单个输入的内存有界，不依赖 -rss_limit_mb
+// cargo fuzz run image_decode -- -max_len=1048576
+
+use barcode_detector::service::barcode::detect_and_decode_with_options;
+use barcode_detector::service::image::read_gray_mat_from_bytes_with_limits;
+use barcode_detector::service::limits::ImageLimits;
+use barcode_detector::service::options::DecodeOptions;
+use libfuzzer_sys::fuzz_target;
+use opencv::prelude::*;
+
+// 识别流程会放大图像，只允许小图进入，避免单个输入耗时过长
+const LIMITS: ImageLimits = ImageLimits {
+    max_input_bytes: 1024 * 1024,
+    max_width: 2048,
+    max_height: 2048,
+    max_pixels: 1024 * 1024,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let image = match read_gray_mat_from_bytes_with_limits(data, &LIMITS) {
+        Ok(image) => image,
+        Err(_) => return,
+    };
+    // 解码出的尺寸不能超过头部检查时放行的限制
+    let pixels = image.rows() as u64 * image.cols() as u64;
+    assert!(pixels <= LIMITS.max_pixels, "decoded {}x{} exceeds the pixel limit", image.cols(), image.rows());
+    let options = DecodeOptions {
+        timeout_ms: Some(2000),
+        ..DecodeOptions::default()
+    };
+    let _ = detect_and_decode_with_options(&image, &options);
+});
@@ -1,9 +1,10 @@
-mod basic;
-mod service;
-
-use std::{env, io};
+use barcode_detector::service;
+use barcode_detector::service::encoder::{RenderOptions, Symbology};
+use barcode_detector::service::options::{CancellationToken, DecodeOptions};
+use std::{env, fs, io, process, thread};
 use std::io::Write;
 use std::path::Path;
+use std::time::Duration;
 
 
 
@@ -17,24 +18,164 @@ fn main() {
     if args.len() < 1{
         panic!("Please specify the path to save the image");
     }
-    let img_path_str = &args[1];
-    let pp = Path::new(img_path_str.as_str());
-    let mut img_path = pp.to_path_buf();
-    if pp.is_relative() {
-        let crt = env::current_dir().unwrap();
-        img_path = crt.join(pp);
+    if args.get(1).map(String::as_str) == Some("generate") {
+        generate(&args[2..]);
+        return;
+    }
+    // 可选参数：
+    // --waybill-rules <path>：从 JSON 文件加载自定义的运单号识别规则
+    // --timeout-ms <N>：单张图片的识别时间预算
+    // --batch-timeout-ms <N>：批量识别多张图片的总时限，到时取消正在识别的图片并跳过剩余图片
+    let mut img_paths = Vec::new();
+    let mut options = DecodeOptions::default();
+    let mut batch_timeout_ms = None;
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--waybill-rules" => {
+                let rules_path = iter.next().expect("Please specify the path of waybill rules");
+                match options.waybill_rules.load(rules_path) {
+                    Ok(count) => println!("Loaded {} waybill rules", count),
+                    Err(e) => {
+                        println!("Failed to load waybill rules: {:?}", e);
+                        return;
+                    }
+                }
+            }
+            "--timeout-ms" => options.timeout_ms = Some(number_arg(arg, iter.next())),
+            "--batch-timeout-ms" => batch_timeout_ms = Some(number_arg(arg, iter.next())),
+            _ => img_paths.push(arg.as_str()),
+        }
+    }
+    if img_paths.is_empty() {
+        println!("Please specify the path of the image, or - to read from stdin");
+        process::exit(2);
+    }
+    // 批量识别共用一个取消标记，由后台线程在总时限到达时触发
+    if let Some(ms) = batch_timeout_ms {
+        let token = CancellationToken::new();
+        let watchdog = token.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(ms));
+            watchdog.cancel();
+        });
+        options.cancellation = Some(token);
+    }
+    for img_path_str in img_paths {
+        if options.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled) {
+            println!("Batch time limit reached, skipped {}", img_path_str);
+            continue;
+        }
+        detect_image(img_path_str, &options);
+    }
+}
+
+// 解析数字参数，缺失或不是数字时打印用法错误并以非 0 状态退出
+fn number_arg<T: std::str::FromStr>(name: &str, value: Option<&String>) -> T {
+    match value.and_then(|v| v.parse::<T>().ok()) {
+        Some(n) => n,
+        None => {
+            println!("Please specify a number after {}", name);
+            process::exit(2);
+        }
     }
-    println!("Image path: {:?}", img_path);
-    io::stdout().flush().unwrap(); // 手动刷新
+}
 
-    let gray_image = service::image::read_gray_mat_from_path(img_path.to_str().unwrap()).unwrap();
-    let result = service::barcode::detect_and_decode(&gray_image);
+fn detect_image(img_path_str: &str, options: &DecodeOptions) {
+    let gray_image = if img_path_str == "-" {
+        // 从标准输入读取图片数据，如 curl ... | barcode-detector -
+        println!("Image path: <stdin>");
+        match service::image::read_gray_mat_from_reader(io::stdin().lock()) {
+            Ok(image) => image,
+            Err(e) => {
+                println!("Failed to read image from stdin: {}", e);
+                return;
+            }
+        }
+    } else {
+        let pp = Path::new(img_path_str);
+        let mut img_path = pp.to_path_buf();
+        if pp.is_relative() {
+            let crt = env::current_dir().unwrap();
+            img_path = crt.join(pp);
+        }
+        println!("Image path: {:?}", img_path);
+        io::stdout().flush().unwrap(); // 手动刷新
+
+        match service::image::read_gray_mat_from_path(img_path.to_str().unwrap()) {
+            Ok(image) => image,
+            Err(e) => {
+                println!("Failed to read image: {}", e);
+                return;
+            }
+        }
+    };
+    let result = service::barcode::detect_and_decode_with_options(&gray_image, options);
     if result.is_err() {
         println!("Failed to detect and decode barcodes: {:?}", result.err().unwrap());
         return;
     }
-    let codes = result.unwrap();
-    for code in codes {
+    let result = result.unwrap();
+    for code in result.codes {
         println!("Detected barcode: {:?}", code);
     }
-}
\ No newline at end of file
+    for message in result.messages {
+        println!("Structured append message: {:?}", message);
+    }
+    if result.timed_out {
+        println!("Detection stopped early, results may be incomplete");
+    }
+}
+
+// generate <码制> <内容> <输出文件.png|.svg> [--module-size N] [--quiet-zone N] [--height N] [--no-text]
+fn generate(args: &[String]) {
+    const USAGE: &str = "Usage: generate <ean13|upca|code128|code39|itf|qr> <data> <output.png|output.svg> [--module-size N] [--quiet-zone N] [--height N] [--no-text]";
+    if args.len() < 3 {
+        println!("{}", USAGE);
+        process::exit(2);
+    }
+    let symbology = match Symbology::from_name(&args[0]) {
+        Some(s) => s,
+        None => {
+            println!("Unknown symbology: {}\n{}", args[0], USAGE);
+            process::exit(2);
+        }
+    };
+    let mut options = RenderOptions::default();
+    let mut iter = args[3..].iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--module-size" => options.module_size = number_arg(arg, iter.next()),
+            "--quiet-zone" => options.quiet_zone = Some(number_arg(arg, iter.next())),
+            "--height" => options.bar_height = number_arg(arg, iter.next()),
+            "--no-text" => options.human_readable = false,
+            other => {
+                println!("Unknown option: {}\n{}", other, USAGE);
+                process::exit(2);
+            }
+        }
+    }
+
+    let output = &args[2];
+    let result = service::encoder::encode(symbology, &args[1], &options).and_then(|encoded| {
+        let bytes = if output.to_ascii_lowercase().ends_with(".svg") {
+            service::encoder::render_svg(&encoded, &options)?.into_bytes()
+        } else {
+            service::encoder::render_png(&encoded, &options)?
+        };
+        Ok((encoded, bytes))
+    });
+    match result {
+        Ok((encoded, bytes)) => match fs::write(output, bytes) {
+            Ok(_) => println!("Generated {} {:?} to {}", encoded.symbology.category(), encoded.text, output),
+            Err(e) => {
+                println!("Failed to write {}: {}", output, e);
+                process::exit(1);
+            }
+        },
+        Err(e) => {
+            println!("Failed to generate barcode: {:?}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use crate::basic::Exception;
+use crate::service::calendar::{is_valid_date, today};
+use crate::service::dto::DriverLicense;
+
+// AAMVA DL/ID 卡设计标准（2000~2020 版）PDF417 数据：
+// @ LF RS CR + "ANSI "（01 版为 "AAMVA"）+ IIN(6) + 版本(2) + 发证机构版本(2，01 版没有) + 子文件数(2)，
+// 随后每个子文件 10 个字符：类型(2) + 偏移(4) + 长度(4)；子文件内每个数据元素占一行，3 个字母的 ID 后接取值
+
+struct Subfile {
+    kind: String,
+    offset: usize,
+    length: usize,
+}
+
+fn number<T: std::str::FromStr>(text: &str, range: std::ops::Range<usize>, what: &str) -> Result<T, Exception> {
+    text.get(range)
+        .filter(|v| v.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|v| v.parse::<T>().ok())
+        .ok_or_else(|| Exception::new(0, format!("Invalid AAMVA header: bad {}", what)))
+}
+
+// 子文件按头部给出的偏移定位，偏移与实际内容不符（常见于部分发证机构）时退而按类型查找
+fn locate_subfile<'a>(text: &'a str, subfile: &Subfile, header_end: usize, errors: &mut Vec<String>) -> Option<&'a str> {
+    let kind = subfile.kind.as_str();
+    if text.get(subfile.offset..subfile.offset + 2) == Some(kind) {
+        let end = (subfile.offset + subfile.length).min(text.len());
+        return text.get(subfile.offset + 2..end).or_else(|| text.get(subfile.offset + 2..));
+    }
+    errors.push(format!("Subfile {} offset {} does not match its content", kind, subfile.offset));
+    let start = header_end + text.get(header_end..)?.find(kind)?;
+    text.get(start + 2..)
+}
+
+// 只保留 D 开头的 DL/ID 数据元素，同一 ID 以首次出现的为准；NONE、unavl 等占位值视为缺失
+fn parse_elements(body: &str) -> HashMap<String, String> {
+    let mut elements = HashMap::new();
+    for line in body.split(['\n', '\r']) {
+        let id = match line.get(..3) {
+            Some(id) if id.starts_with('D') && id.bytes().all(|b| b.is_ascii_uppercase()) => id,
+            _ => continue,
+        };
+        let value = line[3..].trim();
+        if value.is_empty() || value.eq_ignore_ascii_case("NONE") || value.eq_ignore_ascii_case("unavl") || value.eq_ignore_ascii_case("unavail") {
+            continue;
+        }
+        elements.entry(id.to_string()).or_insert_with(|| value.to_string());
+    }
+    elements
+}
+
+// 美国自 02 版起使用 MMDDCCYY，加拿大及 01 版使用 CCYYMMDD；按首选格式解析失败时尝试另一种
+fn parse_date(value: &str, prefer_ymd: bool) -> Option<(i32, u32, u32)> {
+    if value.len() != 8 || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let field = |range: std::ops::Range<usize>| value[range].parse::<u32>().unwrap_or(0);
+    let ymd = (field(0..4) as i32, field(4..6), field(6..8));
+    let mdy = (field(4..8) as i32, field(0..2), field(2..4));
+    let (first, second) = if prefer_ymd { (ymd, mdy) } else { (mdy, ymd) };
+    [first, second].into_iter().find(|&(y, m, d)| is_valid_date(y, m, d))
+}
+
+fn iso_date((year, month, day): (i32, u32, u32)) -> String {
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn age_on(birth: (i32, u32, u32), date: (i32, u32, u32)) -> Option<u32> {
+    let years = date.0 - birth.0 - if (date.1, date.2) < (birth.1, birth.2) { 1 } else { 0 };
+    u32::try_from(years).ok()
+}
+
+fn sex(value: &str) -> String {
+    match value {
+        "1" => "M".to_string(),
+        "2" => "F".to_string(),
+        "9" => "X".to_string(),
+        other => other.to_ascii_uppercase(),
+    }
+}
+
+// 01 版的 DAA 全名为“姓,名,中间名”，02、03 版的 DCT 为“名,中间名”或以空格分隔
+fn split_name(value: &str) -> Vec<String> {
+    let separator = if value.contains(',') { ',' } else { ' ' };
+    value.split(separator).map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect()
+}
+
+pub fn parse_aamva(text: &str) -> Result<DriverLicense, Exception> {
+    parse_aamva_on(text, today())
+}
+
+// 以指定日期（年, 月, 日）计算周岁与是否过期，便于按业务日期或在测试中复现结果
+pub fn parse_aamva_on(text: &str, today: (i32, u32, u32)) -> Result<DriverLicense, Exception> {
+    if !text.starts_with('@') {
+        return Err(Exception::new(0, "Not an AAMVA payload: missing compliance indicator"));
+    }
+    let header_start = text
+        .find("ANSI ")
+        .or_else(|| text.find("AAMVA"))
+        .ok_or_else(|| Exception::new(0, "Not an AAMVA payload: missing file type"))?;
+    let mut errors = Vec::new();
+    // 合规标识应为 @ LF RS CR，部分解码器会丢失其中的控制字符，只记录不中止
+    if text.get(1..4) != Some("\n\u{1E}\r") {
+        errors.push("Compliance indicator is not @<LF><RS><CR>".to_string());
+    }
+
+    let header = &text[header_start + 5..];
+    let iin = number::<u32>(header, 0..6, "IIN").map(|_| header[0..6].to_string())?;
+    let version = number::<u8>(header, 6..8, "version")?;
+    let (jurisdiction_version, mut pos) = if version >= 2 {
+        (Some(number::<u8>(header, 8..10, "jurisdiction version")?), 10)
+    } else {
+        (None, 8)
+    };
+    let entries = number::<usize>(header, pos..pos + 2, "number of entries")?;
+    pos += 2;
+    if !(1..=10).contains(&version) {
+        errors.push(format!("Unknown AAMVA version {:02}", version));
+    }
+
+    let mut subfiles = Vec::new();
+    for _ in 0..entries {
+        let kind = header.get(pos..pos + 2).ok_or_else(|| Exception::new(0, "Invalid AAMVA header: truncated subfile designator"))?;
+        subfiles.push(Subfile {
+            kind: kind.to_string(),
+            offset: number(header, pos + 2..pos + 6, "subfile offset")?,
+            length: number(header, pos + 6..pos + 10, "subfile length")?,
+        });
+        pos += 10;
+    }
+    let primary = subfiles
+        .iter()
+        .find(|s| s.kind == "DL" || s.kind == "ID")
+        .ok_or_else(|| Exception::new(0, "AAMVA payload has no DL or ID subfile"))?;
+    let body = locate_subfile(text, primary, header_start + 5 + pos, &mut errors).ok_or_else(|| Exception::new(0, format!("AAMVA subfile {} not found", primary.kind)))?;
+    let elements = parse_elements(body);
+    let get = |id: &str| elements.get(id).cloned();
+
+    // 姓名：01 版为 DAB/DAC/DAD 或 DAA 全名，02、03 版为 DCS/DCT，04 版起为 DCS/DAC/DAD
+    let full_name = get("DAA").map(|n| split_name(&n)).unwrap_or_default();
+    let given_names = get("DCT").map(|n| split_name(&n)).unwrap_or_default();
+    let family_name = get("DCS").or_else(|| get("DAB")).or_else(|| full_name.first().cloned());
+    let given_name = get("DAC").or_else(|| given_names.first().cloned()).or_else(|| full_name.get(1).cloned());
+    let middle_name = get("DAD").or_else(|| given_names.get(1).cloned()).or_else(|| full_name.get(2).cloned());
+
+    let country = get("DCG");
+    let prefer_ymd = version == 1 || country.as_deref() == Some("CAN");
+    let mut date = |id: &str, name: &str| {
+        let value = get(id)?;
+        let parsed = parse_date(&value, prefer_ymd);
+        if parsed.is_none() {
+            errors.push(format!("Invalid {} ({}) {:?}", name, id, value));
+        }
+        parsed
+    };
+    let birth = date("DBB", "date of birth");
+    let issue = date("DBD", "issue date");
+    let expiry = date("DBA", "expiry date");
+
+    let mut license = DriverLicense {
+        version,
+        jurisdiction_version,
+        iin,
+        document_type: primary.kind.clone(),
+        license_number: get("DAQ"),
+        family_name,
+        given_name,
+        middle_name,
+        date_of_birth: birth.map(iso_date),
+        issue_date: issue.map(iso_date),
+        expiry_date: expiry.map(iso_date),
+        sex: get("DBC").map(|s| sex(&s)),
+        street: get("DAG"),
+        city: get("DAI"),
+        jurisdiction: get("DAJ"),
+        postal_code: get("DAK"),
+        country,
+        vehicle_class: get("DCA"),
+        document_discriminator: get("DCF"),
+        age: birth.and_then(|b| age_on(b, today)),
+        expired: expiry.map(|e| e < today),
+        errors,
+    };
+    for (id, name, missing) in [
+        ("DAQ", "license number", license.license_number.is_none()),
+        ("DCS", "family name", license.family_name.is_none()),
+        ("DAC", "given name", license.given_name.is_none()),
+        ("DBB", "date of birth", license.date_of_birth.is_none()),
+        ("DBA", "expiry date", license.expiry_date.is_none()),
+        ("DAJ", "jurisdiction", license.jurisdiction.is_none()),
+    ] {
+        if missing && !elements.contains_key(id) {
+            license.errors.push(format!("Missing {} ({})", name, id));
+        }
+    }
+    Ok(license)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 08 版加州驾照样例，头部 41 字节，DL 子文件偏移 41、长度 164，ZC 子文件偏移 205、长度 26
+    const DL_SAMPLE: &str = concat!(
+        "@\n\u{1E}\rANSI 636014080102DL00410164ZC02050026",
+        "DLDAQD12345678\nDCSPUBLIC\nDACJOHN\nDADQUINCY\nDBD08242015\nDBB01311970\nDBA01312035\nDBC1\n",
+        "DAG789 E OAK ST\nDAIANYTOWN\nDAJCA\nDAK902230000  \nDCGUSA\nDCAC\nDCF83D9BN217QO983B1\r",
+        "ZCZCAY\nZCBCORRECTIVE LENS\r",
+    );
+
+    const TODAY: (i32, u32, u32) = (2026, 1, 30);
+
+    #[test]
+    fn parses_driver_license() {
+        let license = parse_aamva_on(DL_SAMPLE, TODAY).unwrap();
+        assert_eq!(license.version, 8);
+        assert_eq!(license.jurisdiction_version, Some(1));
+        assert_eq!(license.iin, "636014");
+        assert_eq!(license.document_type, "DL");
+        assert_eq!(license.license_number.as_deref(), Some("D12345678"));
+        assert_eq!((license.family_name.as_deref(), license.given_name.as_deref(), license.middle_name.as_deref()), (Some("PUBLIC"), Some("JOHN"), Some("QUINCY")));
+        // 美国 02 版起日期为 MMDDCCYY
+        assert_eq!(license.date_of_birth.as_deref(), Some("1970-01-31"));
+        assert_eq!(license.issue_date.as_deref(), Some("2015-08-24"));
+        assert_eq!(license.expiry_date.as_deref(), Some("2035-01-31"));
+        assert_eq!(license.sex.as_deref(), Some("M"));
+        assert_eq!(license.street.as_deref(), Some("789 E OAK ST"));
+        assert_eq!(license.city.as_deref(), Some("ANYTOWN"));
+        assert_eq!(license.jurisdiction.as_deref(), Some("CA"));
+        assert_eq!(license.postal_code.as_deref(), Some("902230000"));
+        assert_eq!(license.country.as_deref(), Some("USA"));
+        assert_eq!(license.vehicle_class.as_deref(), Some("C"));
+        assert_eq!(license.document_discriminator.as_deref(), Some("83D9BN217QO983B1"));
+        assert_eq!(license.age, Some(55));
+        assert_eq!(license.expired, Some(false));
+        assert!(license.errors.is_empty(), "{:?}", license.errors);
+    }
+
+    #[test]
+    fn parses_version_one_full_name_and_dates() {
+        let text = "@\n\u{1E}\rAAMVA6360000101DL00290060DLDAQ1234567\nDAAPUBLIC,JOHN,Q\nDBB19700131\nDBA20350131\nDAJVA\r";
+        let license = parse_aamva_on(text, TODAY).unwrap();
+        assert_eq!((license.version, license.jurisdiction_version), (1, None));
+        assert_eq!((license.family_name.as_deref(), license.given_name.as_deref(), license.middle_name.as_deref()), (Some("PUBLIC"), Some("JOHN"), Some("Q")));
+        // 01 版日期为 CCYYMMDD
+        assert_eq!(license.date_of_birth.as_deref(), Some("1970-01-31"));
+        assert_eq!(license.expiry_date.as_deref(), Some("2035-01-31"));
+        assert!(license.errors.is_empty(), "{:?}", license.errors);
+    }
+
+    #[test]
+    fn falls_back_when_subfile_offset_is_wrong() {
+        let text = DL_SAMPLE.replacen("DL0041", "DL0040", 1);
+        let license = parse_aamva_on(&text, TODAY).unwrap();
+        assert_eq!(license.license_number.as_deref(), Some("D12345678"));
+        assert_eq!(license.errors, ["Subfile DL offset 40 does not match its content"]);
+    }
+
+    #[test]
+    fn reports_missing_and_invalid_elements() {
+        let text = DL_SAMPLE.replacen("DBB01311970", "DBB13311970", 1).replacen("DAQD12345678\n", "", 1);
+        let license = parse_aamva_on(&text, TODAY).unwrap();
+        assert_eq!(license.date_of_birth, None);
+        assert_eq!(license.age, None);
+        assert!(license.errors.iter().any(|e| e.starts_with("Invalid date of birth (DBB)")));
+        assert!(license.errors.iter().any(|e| e == "Missing license number (DAQ)"));
+    }
+
+    #[test]
+    fn rejects_non_aamva_payloads() {
+        assert!(parse_aamva("ANSI 636014080102DL00410164").is_err());
+        assert!(parse_aamva("@\n\u{1E}\rhello").is_err());
+        assert!(parse_aamva("@\n\u{1E}\rANSI 6360140801").is_err());
+    }
+
+    #[test]
+    fn expiry_and_age_follow_the_given_date() {
+        let license = parse_aamva_on(DL_SAMPLE, (2035, 1, 31)).unwrap();
+        assert_eq!((license.age, license.expired), (Some(65), Some(false)));
+        let license = parse_aamva_on(DL_SAMPLE, (2035, 2, 1)).unwrap();
+        assert_eq!((license.age, license.expired), (Some(65), Some(true)));
+    }
+
+    #[test]
+    fn age_counts_completed_years() {
+        assert_eq!(age_on((1970, 1, 31), (2026, 1, 30)), Some(55));
+        assert_eq!(age_on((1970, 1, 31), (2026, 1, 31)), Some(56));
+        assert_eq!(age_on((2030, 1, 1), (2026, 1, 1)), None);
+    }
+}
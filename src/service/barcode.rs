@@ -1,10 +1,31 @@
-use opencv::core::{add_weighted, convert_scale_abs, flip, DecompTypes, Mat, MatTraitConst, Point2f, Scalar, Size, Vector, BORDER_CONSTANT, BORDER_DEFAULT, CV_16S, CV_8U};
-use opencv::imgcodecs::imwrite;
+use opencv::core::{add_weighted, convert_scale_abs, flip, DecompTypes, Mat, MatTraitConst, MatTraitConstManual, Point2f, Scalar, Size, Vector, BORDER_CONSTANT, BORDER_DEFAULT, CV_16S, CV_8U};
 use opencv::imgproc;
 use opencv::imgproc::{adaptive_threshold, equalize_hist, gaussian_blur, laplacian, get_perspective_transform, sobel, threshold, warp_perspective, ADAPTIVE_THRESH_GAUSSIAN_C, INTER_LINEAR, THRESH_BINARY, THRESH_OTSU, morphology_ex, MORPH_CLOSE, create_clahe, CLAHETrait, MORPH_RECT, get_structuring_element, resize};
-use opencv::objdetect::{BarcodeDetector, GraphicalCodeDetectorTraitConst};
+use opencv::objdetect::{BarcodeDetector, BarcodeDetectorTrait, GraphicalCodeDetectorTraitConst};
+use std::path::Path;
+use std::cell::Cell;
+use std::time::{Duration, Instant};
 use crate::basic::Exception;
-use crate::service::dto::{CodeInfo, Point};
+use crate::service::charset::detect_text;
+use crate::service::dto::{CodeInfo, DetectResult, Dimension, Point, StageTimings};
+use crate::service::options::{CancellationToken, DecodeOptions, DetectorOptions, MatrixFormat, ScaleOptions};
+use crate::service::superres::SuperResolver;
+use crate::service::content::parse_content;
+use crate::service::gs1::parse_code;
+use crate::service::geometry::{compute_geometry, match_modules, module_width_from_row, orientation_from_angle};
+use crate::service::matrix::{decode_matrix_region, detect_and_decode_matrix, MatrixResult};
+use crate::service::structured::reassemble_structured_append;
+use crate::service::upcean::{candidate_patterns, decode_addon, product_info};
+
+// 将竖向（高大于宽）的图像顺时针旋转 90 度摆正为横向。
+// extract_and_rotate_if_needed 与 extract_and_expand 共用，保证两者摆正方向一致，
+// 摆正后从左到右的方向即 geometry::reading_axis 给出的方向
+fn rotate_to_landscape(output: &mut Mat) -> opencv::Result<()> {
+    let mut rotated = Mat::default();
+    opencv::core::transpose(&*output, &mut rotated)?; // 转置
+    opencv::core::flip(&rotated, output, 1)?;  // 沿垂直轴翻转，与转置合起来即顺时针旋转90度
+    Ok(())
+}
 
 fn extract_and_rotate_if_needed(image: &Mat, points: &Vec<Point2f>) -> opencv::Result<Mat> {
     let mut src_points = Vector::<Point2f>::new();
@@ -45,83 +66,140 @@ fn extract_and_rotate_if_needed(image: &Mat, points: &Vec<Point2f>) -> opencv::R
 
     // 如果裁切后的区域是长方形并且高度大于宽度，则旋转90度
     if max_height > max_width {
-        let mut rotated = Mat::default();
-        opencv::core::transpose(&output, &mut rotated)?; // 转置操作相当于旋转90度
-        opencv::core::flip(&rotated, &mut output, 1)?;  // 沿垂直轴翻转
+        rotate_to_landscape(&mut output)?;
     }
 
     Ok(output)
 }
 
-fn extract_and_expand(image: &Mat, points: &Vec<Point2f>) -> opencv::Result<Mat> {
-    let mut src_points = Vector::<Point2f>::new();
-    for point in points {
-        src_points.push(*point);
-    }
-
-    // 计算输出的目标矩形宽高
-    let width_a = ((points[1].x - points[0].x).powi(2) + (points[1].y - points[0].y).powi(2)).sqrt();
-    let width_b = ((points[2].x - points[3].x).powi(2) + (points[2].y - points[3].y).powi(2)).sqrt();
-    let max_width = width_a.max(width_b) as f32;
-
-    let height_a = ((points[0].x - points[3].x).powi(2) + (points[0].y - points[3].y).powi(2)).sqrt();
-    let height_b = ((points[1].x - points[2].x).powi(2) + (points[1].y - points[2].y).powi(2)).sqrt();
-    let max_height = height_a.max(height_b) as f32;
-
-    // 计算中心点
-    let center_x = (points[0].x + points[2].x) / 2.0;
-    let center_y = (points[0].y + points[2].y) / 2.0;
-
-    // 扩展区域：我们将每个顶点从中心点向外扩展50%
-    let scale_factor = 2f32; // 扩展比例为50%
-    let expanded_points = points
+// 沿条码自身的坐标轴向外扩展四个角点：horizontal 沿阅读方向（长边），vertical 沿条高方向，单位为像素。
+// 扩展后的角点限制在图像范围内，避免透视变换时用黑边填充破坏静区
+fn expand_points(points: &[Point2f], horizontal: f32, vertical: f32, image_size: Size) -> Vec<Point2f> {
+    let unit = |a: &Point2f, b: &Point2f| {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let length = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+        (dx / length, dy / length)
+    };
+    // u 为 points[0]->points[1] 方向，v 为 points[0]->points[3] 方向
+    let u = unit(&points[0], &points[1]);
+    let v = unit(&points[0], &points[3]);
+
+    let width = ((points[1].x - points[0].x).powi(2) + (points[1].y - points[0].y).powi(2)).sqrt();
+    let height = ((points[0].x - points[3].x).powi(2) + (points[0].y - points[3].y).powi(2)).sqrt();
+    let (margin_u, margin_v) = if width >= height { (horizontal, vertical) } else { (vertical, horizontal) };
+
+    let max_x = (image_size.width - 1).max(0) as f32;
+    let max_y = (image_size.height - 1).max(0) as f32;
+    let signs = [(-1f32, -1f32), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+    points
         .iter()
-        .map(|p| {
-            Point2f::new(
-                center_x + (p.x - center_x) * 1f32,
-                center_y + (p.y - center_y) * scale_factor,
-            )
+        .zip(signs.iter())
+        .map(|(p, (su, sv))| {
+            let x = p.x + su * margin_u * u.0 + sv * margin_v * v.0;
+            let y = p.y + su * margin_u * u.1 + sv * margin_v * v.1;
+            Point2f::new(x.clamp(0.0, max_x), y.clamp(0.0, max_y))
         })
-        .collect::<Vec<Point2f>>();
+        .collect::<Vec<Point2f>>()
+}
 
-    // 将扩展后的四个顶点存储到 src_points
-    let mut expanded_src_points = Vector::<Point2f>::new();
-    for point in &expanded_points {
-        expanded_src_points.push(*point);
+pub fn extract_and_expand(image: &Mat, points: &Vec<Point2f>, horizontal: f32, vertical: f32) -> opencv::Result<Mat> {
+    let expanded_points = expand_points(points, horizontal, vertical, image.size()?);
+    extract_and_rotate_if_needed(image, &expanded_points)
+}
+
+// 读取一行灰度像素
+fn read_row(image: &Mat, y: i32) -> opencv::Result<Vec<u8>> {
+    let mut row = Vec::<u8>::with_capacity(image.cols() as usize);
+    for x in 0..image.cols() {
+        row.push(*image.at_2d::<u8>(y, x)?);
     }
+    Ok(row)
+}
 
-    // 目标矩形的四个顶点，保持扩展后宽高比
-    let mut dst_points = Vector::<Point2f>::new();
-    dst_points.push(Point2f::new(0.0, 0.0));
-    dst_points.push(Point2f::new(max_width * scale_factor, 0.0));
-    dst_points.push(Point2f::new(max_width * scale_factor, max_height * scale_factor));
-    dst_points.push(Point2f::new(0.0, max_height * scale_factor));
+// 横穿已摆正（横向）条码图像的扫描线位置：1/4、1/2、3/4 高度处
+fn scan_rows(image: &Mat) -> Vec<i32> {
+    let rows = image.rows();
+    if rows == 0 || image.cols() == 0 {
+        return Vec::new();
+    }
+    vec![rows / 4, rows / 2, rows * 3 / 4]
+}
 
-    // 计算透视变换矩阵
-    let perspective_matrix = get_perspective_transform(&expanded_src_points, &dst_points, 0)?;
+// 在已摆正（横向）的条码图像上取若干横向扫描线估计模块宽度，取中位数
+fn estimate_module_width(code_image: &Mat) -> opencv::Result<Option<f32>> {
+    let mut estimates = Vec::<f32>::new();
+    for y in scan_rows(code_image) {
+        let row = read_row(code_image, y)?;
+        if let Some(width) = module_width_from_row(&row) {
+            estimates.push(width);
+        }
+    }
+    if estimates.is_empty() {
+        return Ok(None);
+    }
+    estimates.sort_by(f32::total_cmp);
+    Ok(Some(estimates[estimates.len() / 2]))
+}
 
-    // 透视变换后的图像
-    let mut output = Mat::default();
-    warp_perspective(
-        image,
-        &mut output,
-        &perspective_matrix,
-        Size::new((max_width * scale_factor) as i32, (max_height * scale_factor) as i32),
-        INTER_LINEAR,
-        0,
-        Scalar::default(),
-    )?;
+// 用解码文本重新编码出各候选码制的期望条空序列，与摆正图像的扫描线正反向比对，
+// 返回吻合度最高的码制以及阅读方向是否与摆正后的从左到右相反。
+// OpenCV 的一维码解码器会自动尝试两个方向，无法直接给出阅读方向，因此在解码之后单独判断
+fn detect_reading_direction(code_image: &Mat, text: &str) -> opencv::Result<Option<(&'static str, bool)>> {
+    let rows = scan_rows(code_image);
+    if rows.is_empty() {
+        return Ok(None);
+    }
+    let mut lines = Vec::<Vec<u8>>::new();
+    for y in rows {
+        lines.push(read_row(code_image, y)?);
+    }
 
-    // 如果裁切后的区域是长方形并且高度大于宽度，则旋转90度
-    if max_height > max_width {
-        let mut rotated = Mat::default();
-        opencv::core::transpose(&output, &mut rotated)?; // 转置操作相当于旋转90度
-        opencv::core::flip(&rotated, &mut output, 0)?;  // 0表示垂直翻转
+    let mut best: Option<(&'static str, f32, f32)> = None;
+    for (category, expected) in candidate_patterns(text) {
+        let (mut forward, mut reversed, mut count) = (0f32, 0f32, 0usize);
+        for line in &lines {
+            if let Some((f, r)) = match_modules(line, &expected) {
+                forward += f;
+                reversed += r;
+                count += 1;
+            }
+        }
+        if count == 0 {
+            continue;
+        }
+        let (forward, reversed) = (forward / count as f32, reversed / count as f32);
+        if best.is_none_or(|b| forward.max(reversed) > b.1.max(b.2)) {
+            best = Some((category, forward, reversed));
+        }
     }
 
-    Ok(output)
+    // 吻合度不足或正反向难以区分时不作判断
+    Ok(best.and_then(|(category, forward, reversed)| {
+        if forward.max(reversed) < 0.85 || (forward - reversed).abs() < 0.05 {
+            None
+        } else {
+            Some((category, reversed > forward))
+        }
+    }))
 }
 
+// 附加码位于主码阅读方向右侧 7~12 个模块处，长 20（EAN-2）或 47（EAN-5）个模块，条高略短于主码且底部对齐。
+// 沿阅读方向两侧各扩展 70 个模块后，在靠近条码底部的几条扫描线上寻找；reversed 时摆正后的图像上下左右均颠倒
+fn detect_addon(gray_image: &Mat, code_points: &Vec<Point2f>, module_width: f32, reversed: bool) -> opencv::Result<Option<String>> {
+    let wide_image = extract_and_expand(gray_image, code_points, module_width * 70.0, 0.0)?;
+    let rows = wide_image.rows();
+    for y in [rows * 3 / 5, rows * 3 / 4, rows * 9 / 10] {
+        let y = if reversed { rows - 1 - y } else { y };
+        let mut row = read_row(&wide_image, y.clamp(0, (rows - 1).max(0)))?;
+        if reversed {
+            row.reverse();
+        }
+        if let Some(addon) = decode_addon(&row, module_width) {
+            return Ok(Some(addon));
+        }
+    }
+    Ok(None)
+}
 
 fn enhance_vertical_lines(gray_image: &Mat) -> opencv::Result<Mat> {
     // 1. 使用 CLAHE 自适应直方图均衡化提高对比度
@@ -153,20 +231,41 @@ fn enhance_vertical_lines(gray_image: &Mat) -> opencv::Result<Mat> {
     Ok(result)
 }
 
-fn enhance_vertical_lines_with_scaling(gray_image: &Mat) -> opencv::Result<Mat> {
-    // 1. 等比放大图像（放大2倍）
-    let mut scaled_image = Mat::default();
-    let scale_factor = 10.0; // 可以根据需要调整放大倍数
-    resize(&gray_image, &mut scaled_image, Size::new(0, 0), scale_factor, scale_factor, INTER_LINEAR)?;
+// 根据模块宽度选择放大倍数：使每个模块达到目标像素数，限制在 [1, max_scale] 内，且放大后总像素数不超过 max_pixels。
+// 无法估计模块宽度时按长边约 100 个模块折算
+fn choose_scale(size: Size, module_width: Option<f32>, options: &ScaleOptions) -> f64 {
+    let module_width = module_width.unwrap_or(size.width.max(size.height) as f32 / 100.0).max(f32::EPSILON);
+    let mut scale = (options.target_pixels_per_module / module_width).clamp(1.0, options.max_scale.max(1.0)) as f64;
+    let pixels = size.width.max(1) as f64 * size.height.max(1) as f64;
+    let max_scale_by_pixels = (options.max_pixels as f64 / pixels).sqrt();
+    if scale > max_scale_by_pixels {
+        scale = max_scale_by_pixels.max(1.0);
+    }
+    scale
+}
 
-    // 2. 调用 enhance_vertical_lines 处理放大后的图像
-    let enhanced_image = enhance_vertical_lines(&scaled_image)?;
+pub fn enhance_vertical_lines_with_scaling(gray_image: &Mat, module_width: Option<f32>, options: &ScaleOptions, resolver: Option<&mut SuperResolver>) -> opencv::Result<Mat> {
+    // 1. 模块过窄时先用超分辨率模型放大，插值放大难以恢复细条
+    let mut source = gray_image.clone();
+    let mut module_width = module_width;
+    if let Some(resolver) = resolver {
+        if module_width.is_some_and(|w| w < resolver.below_module_width) {
+            source = resolver.upsample(gray_image)?;
+            module_width = module_width.map(|w| w * resolver.scale as f32);
+        }
+    }
 
-    // 3. 等比缩小回原始大小
-    let mut final_image = Mat::default();
-    resize(&enhanced_image, &mut final_image, gray_image.size()?, 0.0, 0.0, INTER_LINEAR)?;
+    // 2. 按模块宽度自适应地等比放大图像
+    let scale_factor = choose_scale(source.size()?, module_width, options);
+    let mut scaled_image = Mat::default();
+    if scale_factor > 1.0 {
+        resize(&source, &mut scaled_image, Size::new(0, 0), scale_factor, scale_factor, INTER_LINEAR)?;
+    } else {
+        scaled_image = source;
+    }
 
-    Ok(final_image)
+    // 3. 调用 enhance_vertical_lines 处理放大后的图像，直接以放大后的尺寸参与解码
+    enhance_vertical_lines(&scaled_image)
 }
 
 
@@ -190,43 +289,406 @@ fn enhance_barcode_image(gray_image: &Mat) -> opencv::Result<Mat> {
 }
 
 
+// 放大增强与解码都可能很耗时（如模块过窄时放大 10 倍），两者之前都检查预算，用尽时返回空结果
+fn decode_region(barcode_detector: &BarcodeDetector, code_image: &Mat, module_width: Option<f32>, options: &DecodeOptions, resolver: Option<&mut SuperResolver>, budget: &Budget, timings: &mut StageTimings) -> Result<Vec<u8>, Exception> {
+    if budget.exhausted() {
+        return Ok(Vec::new());
+    }
+    let stage = Instant::now();
+    let enhance_mat = enhance_vertical_lines_with_scaling(code_image, module_width, &options.scale, resolver).map_err(|e| Exception::new(0, &format!("Failed to enhance barcode: {}", e)))?;
+    timings.enhance += elapsed_ms(stage);
+    if budget.exhausted() {
+        return Ok(Vec::new());
+    }
+    let stage = Instant::now();
+    let enhance_points = Vector::<Point2f>::from_slice(&[
+        Point2f::new(0.0, 0.0),
+        Point2f::new(enhance_mat.cols() as f32, 0.0),
+        Point2f::new(enhance_mat.cols() as f32, enhance_mat.rows() as f32),
+        Point2f::new(0.0, enhance_mat.rows() as f32),
+    ]);
+    let mut straight_code = Mat::default();
+    let barcode = barcode_detector.decode(&enhance_mat, &enhance_points, &mut straight_code).map_err(|e| Exception::new(0, &format!("Failed to decode barcode: {}", e)));
+    timings.decode += elapsed_ms(stage);
+    barcode
+}
+
+fn elapsed_ms(start: Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
+// 单次识别的时间预算与取消标记，exhausted 返回 true 后记为超时
+struct Budget<'a> {
+    deadline: Option<Instant>,
+    cancellation: Option<&'a CancellationToken>,
+    timed_out: Cell<bool>,
+}
+
+impl<'a> Budget<'a> {
+    fn new(options: &'a DecodeOptions, started: Instant) -> Self {
+        Self {
+            deadline: options.timeout_ms.map(|ms| started + Duration::from_millis(ms)),
+            cancellation: options.cancellation.as_ref(),
+            timed_out: Cell::new(false),
+        }
+    }
+
+    fn exhausted(&self) -> bool {
+        let exhausted = self.deadline.is_some_and(|d| Instant::now() >= d) || self.cancellation.is_some_and(|c| c.is_cancelled());
+        if exhausted {
+            self.timed_out.set(true);
+        }
+        exhausted
+    }
+}
+
+pub fn create_detector(options: &DetectorOptions) -> Result<BarcodeDetector, Exception> {
+    let mut barcode_detector = match (&options.sr_prototxt, &options.sr_model) {
+        (Some(prototxt), Some(model)) => {
+            for path in [prototxt, model] {
+                if !Path::new(path).is_file() {
+                    return Err(Exception::new(0, format!("Super resolution model file not found: {}", path)));
+                }
+            }
+            BarcodeDetector::new(prototxt, model)
+        }
+        (None, None) => BarcodeDetector::default(),
+        _ => return Err(Exception::new(0, "Both sr_prototxt and sr_model must be set")),
+    }
+    .map_err(|e| Exception::new(0, &format!("Failed to create BarcodeDetector: {}", e)))?;
+
+    if let Some(threshold) = options.downsampling_threshold {
+        barcode_detector.set_downsampling_threshold(threshold).map_err(|e| Exception::new(0, &format!("Failed to set downsampling threshold: {}", e)))?;
+    }
+    if let Some(scales) = &options.detector_scales {
+        barcode_detector.set_detector_scales(&Vector::<f32>::from_slice(scales)).map_err(|e| Exception::new(0, &format!("Failed to set detector scales: {}", e)))?;
+    }
+    if let Some(threshold) = options.gradient_threshold {
+        barcode_detector.set_gradient_threshold(threshold).map_err(|e| Exception::new(0, &format!("Failed to set gradient threshold: {}", e)))?;
+    }
+    Ok(barcode_detector)
+}
+
 pub fn detect_and_decode(gray_image: &Mat) -> Result<Vec<CodeInfo>, Exception> {
-    let barcode_detector = BarcodeDetector::default().map_err(|e| Exception::new(0, &format!("Failed to create BarcodeDetector: {}", e)))?;
+    Ok(detect_and_decode_with_options(gray_image, &DecodeOptions::default())?.codes)
+}
+
+pub fn detect_and_decode_with_options(gray_image: &Mat, options: &DecodeOptions) -> Result<DetectResult, Exception> {
+    let started = Instant::now();
+    let budget = Budget::new(options, started);
+    let mut timings = StageTimings::default();
+    let (small_image, scale) = downscale_if_needed(gray_image, options.max_detect_pixels).map_err(|e| Exception::new(0, format!("Failed to downscale image: {}", e)))?;
+    let detect_image = small_image.as_ref().unwrap_or(gray_image);
+    let mut results = detect_and_decode_linear(gray_image, detect_image, scale, options, &budget, &mut timings)?;
+    let stage = Instant::now();
+    for matrix_result in detect_and_decode_matrix(detect_image, &options.matrix, || budget.exhausted())? {
+        let mut code_info = matrix_code_info(detect_image, matrix_result)?;
+        if scale < 1.0 {
+            restore_coordinates(&mut code_info, 1.0 / scale, gray_image.cols(), gray_image.rows());
+        }
+        if !is_duplicate(&results, &code_info) {
+            results.push(code_info);
+        }
+    }
+    timings.matrix = elapsed_ms(stage);
+    // 超时时即使没有结果也返回 Ok，由 timed_out 区分“未识别”与“未识别完”
+    if results.is_empty() && !budget.timed_out.get() {
+        return Err(Exception::new(0, "No barcode detected"));
+    }
+    let stage = Instant::now();
+    for code in results.iter_mut() {
+        // 校验失败的 GS1 数据不附加解析结果，可调用 gs1::parse_code 获取具体错误
+        code.gs1 = parse_code(code).and_then(Result::ok);
+        code.content = parse_content(&code.code);
+        // 运单号只印在一维码上；商品码、GS1 数据及已识别的内容类型不会是运单号
+        if is_linear_category(&code.category) && code.product.is_none() && code.gs1.is_none() && code.content.is_none() {
+            code.waybill = options.waybill_rules.recognize(&code.code);
+        }
+    }
+    let messages = reassemble_structured_append(&results);
+    timings.post_process = elapsed_ms(stage);
+    timings.total = elapsed_ms(started);
+    Ok(DetectResult {
+        codes: results,
+        messages,
+        timed_out: budget.timed_out.get(),
+        timings: options.profile.then_some(timings),
+    })
+}
+
+// 阅读方向无法确认时，按解码文本唯一对应的码制给出 category（OpenCV 只解码 EAN/UPC，如 13 位数字只能是 EAN-13）；
+// 8 位数字可能是 EAN-8 也可能是 UPC-E，无法区分时为空
+fn unconfirmed_category(text: &str) -> &'static str {
+    match candidate_patterns(text).as_slice() {
+        [(category, _)] => category,
+        _ => "",
+    }
+}
+
+// OpenCV 解出的一维码在码制无法确定时 category 为空，同样视为一维码
+fn is_linear_category(category: &str) -> bool {
+    matches!(category, "" | "EAN_8" | "EAN_13" | "UPC_A" | "UPC_E" | "CODE_128" | "CODE_39" | "ITF")
+}
+
+// 像素数超过 max_pixels 的图像等比缩小后再识别，返回缩小后的图像（无需缩小时为 None）及缩放比例
+fn downscale_if_needed(gray_image: &Mat, max_pixels: Option<u64>) -> opencv::Result<(Option<Mat>, f64)> {
+    let pixels = gray_image.rows().max(0) as u64 * gray_image.cols().max(0) as u64;
+    match max_pixels {
+        Some(max) if pixels > max && max > 0 => {
+            let scale = (max as f64 / pixels as f64).sqrt();
+            let mut small = Mat::default();
+            resize(gray_image, &mut small, Size::new(0, 0), scale, scale, imgproc::INTER_AREA)?;
+            Ok((Some(small), scale))
+        }
+        _ => Ok((None, 1.0)),
+    }
+}
+
+// 把在缩小图像上得到的角点与几何信息换算回原图坐标；角度不随等比缩放变化
+fn restore_coordinates(code: &mut CodeInfo, factor: f64, image_width: i32, image_height: i32) {
+    let factor = factor as f32;
+    for point in code.points.iter_mut() {
+        point.x *= factor;
+        point.y *= factor;
+    }
+    let module_size = code.geometry.module_size.map(|d| Dimension {
+        width: d.width * factor,
+        height: d.height * factor,
+    });
+    code.geometry = compute_geometry(&code.points, image_width, image_height, module_size);
+}
+
+// 同一个符号可能先在一维码区域内解出、又在整图的 rxing 识别中出现：原始字节与文本相同且中心落在已有结果的外接矩形内即视为重复。
+// 无法还原原始字节的二维码 raw 为空，因此同时比较文本
+fn is_duplicate(results: &[CodeInfo], candidate: &CodeInfo) -> bool {
+    let center = &candidate.geometry.center;
+    results.iter().any(|r| {
+        let bbox = &r.geometry.bounding_box;
+        r.raw == candidate.raw
+            && r.code == candidate.code
+            && center.x >= bbox.x
+            && center.x <= bbox.x + bbox.width
+            && center.y >= bbox.y
+            && center.y <= bbox.y + bbox.height
+    })
+}
+
+// 由 rxing 解码的二维码，几何信息与一维码同样从角点推导，朝向取解码器给出的旋转角度
+fn matrix_code_info(gray_image: &Mat, matrix_result: MatrixResult) -> Result<CodeInfo, Exception> {
+    let code_points = matrix_result.points.iter().map(|p| Point2f::new(p.x, p.y)).collect::<Vec<Point2f>>();
+    let module_size = if code_points.len() == 4 {
+        let straight_image = extract_and_rotate_if_needed(gray_image, &code_points).map_err(|e| Exception::new(0, &format!("Failed to straighten barcode: {}", e)))?;
+        let module_width = estimate_module_width(&straight_image).map_err(|e| Exception::new(0, &format!("Failed to estimate module size: {}", e)))?;
+        module_width.map(|width| Dimension { width, height: width })
+    } else {
+        None
+    };
+    let geometry = compute_geometry(&matrix_result.points, gray_image.cols(), gray_image.rows(), module_size);
+    let orientation = match matrix_result.rotation {
+        Some(degrees) => orientation_from_angle(degrees as f32, false, matrix_result.mirrored, true),
+        None => orientation_from_angle(geometry.angle, false, matrix_result.mirrored, false),
+    };
+    Ok(CodeInfo {
+        code: matrix_result.text,
+        raw: matrix_result.raw,
+        charset: matrix_result.charset.map(|c| c.to_string()),
+        eci: matrix_result.eci,
+        symbology_identifier: matrix_result.symbology_identifier,
+        category: matrix_result.category.to_string(),
+        points: matrix_result.points,
+        geometry,
+        orientation,
+        error_correction_level: matrix_result.error_correction_level,
+        macro_pdf417: matrix_result.macro_pdf417,
+        structured_append: matrix_result.structured_append,
+        ..Default::default()
+    })
+}
+
+// OpenCV BarcodeDetector 负责的一维码，没有检测到时返回空列表。
+// 在 detect_image（可能是按 scale 缩小的图像）上检测，角点换算回原图后在原图上提取和解码，缩小会丢失细条
+fn detect_and_decode_linear(gray_image: &Mat, detect_image: &Mat, scale: f64, options: &DecodeOptions, budget: &Budget, timings: &mut StageTimings) -> Result<Vec<CodeInfo>, Exception> {
+    if budget.exhausted() {
+        return Ok(Vec::new());
+    }
+    let stage = Instant::now();
+    let barcode_detector = create_detector(&options.detector)?;
+    let mut resolver = match &options.scale.super_resolution {
+        Some(sr) => Some(SuperResolver::new(sr)?),
+        None => None,
+    };
+    timings.setup = elapsed_ms(stage);
+    if budget.exhausted() {
+        return Ok(Vec::new());
+    }
+    let stage = Instant::now();
     let mut points = Vector::<Point2f>::new();
-    let detect_result = barcode_detector.detect_multi(gray_image, &mut points).map_err(|e| Exception::new(0, &format!("Failed to detect barcodes: {}", e)))?;
+    let detect_result = barcode_detector.detect_multi(detect_image, &mut points).map_err(|e| Exception::new(0, &format!("Failed to detect barcodes: {}", e)))?;
+    timings.detect = elapsed_ms(stage);
     if !detect_result || points.len() < 4 || points.len()%4 != 0 {
-        return Err(Exception::new(0, "No barcode detected"));
+        return Ok(Vec::new());
     }
+    let region_formats = options.matrix.formats.iter().copied().filter(|f| *f == MatrixFormat::Pdf417 || f.is_linear()).collect::<Vec<MatrixFormat>>();
     let mut results = Vec::<CodeInfo>::new();
     for i in 0..points.len()/4 {
+        if budget.exhausted() {
+            break;
+        }
         let mut info_points = Vec::<Point>::new();
         let mut code_points = Vec::new();
         for j in 0..4 {
             let pp = points.get(i * 4 + j).unwrap();
-            code_points.push(Point2f::new(pp.x, pp.y));
+            let (x, y) = ((pp.x as f64 / scale) as f32, (pp.y as f64 / scale) as f32);
+            code_points.push(Point2f::new(x, y));
             info_points.push(Point{
-                x: pp.x,
-                y: pp.y,
+                x,
+                y,
             });
         }
-        let code_image = extract_and_expand(gray_image, &code_points).map_err(|e| Exception::new(0, &format!("Failed to extract barcode: {}", e)))?;
-
-        imwrite(&format!("code_{}.png", i), &code_image, &Vector::new()).map_err(|e| Exception::new(0, &format!("Failed to save barcode: {}", e)))?;
-        let enhance_mat = enhance_vertical_lines_with_scaling(&code_image).map_err(|e| Exception::new(0, &format!("Failed to enhance barcode: {}", e)))?;
-        imwrite(&format!("enhance_{}.png", i), &enhance_mat, &Vector::new()).map_err(|e| Exception::new(0, &format!("Failed to save enhanced barcode: {}", e)))?;
-        let enhance_points = Vector::<Point2f>::from_slice(&[
-            Point2f::new(0.0, 0.0),
-            Point2f::new(code_image.cols() as f32, 0.0),
-            Point2f::new(code_image.cols() as f32, code_image.rows() as f32),
-            Point2f::new(0.0, code_image.rows() as f32),
-        ]);
-        let mut straight_code = Mat::default();
-        let barcode = barcode_detector.decode(&enhance_mat, &enhance_points, &mut straight_code).map_err(|e| Exception::new(0, &format!("Failed to decode barcode: {}", e)))?;
+        // 紧贴角点的摆正图像用于估计模块尺寸，像素比例与原图一致
+        let stage = Instant::now();
+        let straight_image = extract_and_rotate_if_needed(gray_image, &code_points).map_err(|e| Exception::new(0, &format!("Failed to straighten barcode: {}", e)))?;
+        let module_width = estimate_module_width(&straight_image).map_err(|e| Exception::new(0, &format!("Failed to estimate module size: {}", e)))?;
+        let module_size = module_width.map(|width| Dimension {
+            width,
+            height: straight_image.rows() as f32,
+        });
+        let geometry = compute_geometry(&info_points, gray_image.cols(), gray_image.rows(), module_size);
+
+        // 检测框通常紧贴条码，按配置扩展出静区后解码，失败时逐次放大边距重试
+        let long_side = geometry.size.width;
+        let horizontal = options.expand.horizontal.to_pixels(long_side, module_width, long_side);
+        let vertical = options.expand.vertical.to_pixels(geometry.size.height, module_width, long_side);
+        let mut code_image = Mat::default();
+        let mut barcode = Vec::<u8>::new();
+        timings.extract += elapsed_ms(stage);
+        for attempt in 0..=options.expand.retries {
+            if budget.exhausted() {
+                break;
+            }
+            let factor = options.expand.growth.powi(attempt as i32);
+            let stage = Instant::now();
+            code_image = extract_and_expand(gray_image, &code_points, horizontal * factor, vertical * factor).map_err(|e| Exception::new(0, &format!("Failed to extract barcode: {}", e)))?;
+            timings.extract += elapsed_ms(stage);
+            barcode = decode_region(&barcode_detector, &code_image, module_width, options, resolver.as_mut(), budget, timings)?;
+            if !barcode.is_empty() {
+                break;
+            }
+        }
+
+        let stage = Instant::now();
+        // OpenCV 会把 PDF417 的堆叠条当作一维码区域检出但无法解码，Code 128、Code 39、ITF 也只能检出不能解码，
+        // 此时在透视矫正后的区域上交给 rxing：PDF417 由行指示符确定行列后纠错解码，一维码逐行扫描并自动尝试反向。
+        // 只在确实要开始 rxing 解码前检查预算，避免全部工作已完成时仍被记为超时
+        if barcode.is_empty() && !region_formats.is_empty() {
+            if budget.exhausted() {
+                break;
+            }
+            if let Some(stacked) = decode_matrix_region(&code_image, &region_formats, &options.matrix)? {
+                // 区域内的旋转角度相对摆正后的图像，叠加检测框自身的角度
+                let orientation = match stacked.rotation {
+                    Some(degrees) => orientation_from_angle(geometry.angle + degrees as f32, false, stacked.mirrored, true),
+                    None => orientation_from_angle(geometry.angle, false, stacked.mirrored, false),
+                };
+                results.push(CodeInfo {
+                    code: stacked.text,
+                    raw: stacked.raw,
+                    charset: stacked.charset.map(|c| c.to_string()),
+                    eci: stacked.eci,
+                    symbology_identifier: stacked.symbology_identifier,
+                    category: stacked.category.to_string(),
+                    points: info_points,
+                    geometry,
+                    orientation,
+                    error_correction_level: stacked.error_correction_level,
+                    macro_pdf417: stacked.macro_pdf417,
+                    structured_append: stacked.structured_append,
+                    ..Default::default()
+                });
+                timings.decode += elapsed_ms(stage);
+                continue;
+            }
+        }
+        // OpenCV 返回的是原始字节，一维码通常为 ASCII，无法识别为文本时 code 留空
+        let decoded = detect_text(&barcode);
+        let code = decoded.text.unwrap_or_default();
+
+        // 一维码的镜像与旋转 180 度在条空上无法区分，统一按旋转处理，mirrored 恒为 false
+        let direction = detect_reading_direction(&code_image, &code).map_err(|e| Exception::new(0, &format!("Failed to detect orientation: {}", e)))?;
+        let (category, orientation) = match direction {
+            Some((category, reversed)) => (category.to_string(), orientation_from_angle(geometry.angle, reversed, false, true)),
+            None => (unconfirmed_category(&code).to_string(), orientation_from_angle(geometry.angle, false, false, false)),
+        };
+        // EAN-8 没有附加码；阅读方向未确认时无法判断附加码在哪一侧
+        let addon = match (direction, module_width) {
+            (Some((category, reversed)), Some(width)) if category != "EAN_8" => {
+                detect_addon(gray_image, &code_points, width, reversed).map_err(|e| Exception::new(0, &format!("Failed to detect add-on: {}", e)))?
+            }
+            _ => None,
+        };
+        let product = product_info(&category, &code, addon);
+        timings.decode += elapsed_ms(stage);
         results.push(CodeInfo{
-            code: String::from_utf8(barcode).unwrap(),
+            code,
+            raw: barcode,
+            charset: decoded.charset.map(|c| c.to_string()),
             points: info_points,
-            category: String::new(),
+            category,
+            geometry,
+            orientation,
+            product,
+            ..Default::default()
         });
     }
     Ok(results)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unconfirmed_category_from_text() {
+        assert_eq!(unconfirmed_category("4006381333931"), "EAN_13");
+        assert_eq!(unconfirmed_category("036000291452"), "UPC_A");
+        // EAN-8 与 UPC-E 无法区分，其他长度不猜测
+        assert_eq!(unconfirmed_category("01234565"), "");
+        assert_eq!(unconfirmed_category("SF1234567890123"), "");
+        assert!(is_linear_category(unconfirmed_category("SF1234567890123")));
+    }
+
+    #[test]
+    fn scale_follows_module_width() {
+        let options = ScaleOptions::default();
+        let size = Size::new(200, 100);
+        // 每个模块放大到 20 像素，最多 10 倍
+        assert_eq!(choose_scale(size, Some(4.0), &options), 5.0);
+        assert_eq!(choose_scale(size, Some(1.0), &options), 10.0);
+        assert_eq!(choose_scale(size, Some(40.0), &options), 1.0);
+        // 无法估计模块宽度时按长边 100 个模块折算
+        assert_eq!(choose_scale(size, None, &options), 10.0);
+    }
+
+    #[test]
+    fn scale_is_capped_by_pixel_budget() {
+        let options = ScaleOptions::default();
+        assert_eq!(choose_scale(Size::new(2000, 1000), Some(2.0), &options), 2.0);
+        // 原图已超出像素上限时不再放大，也不缩小
+        assert_eq!(choose_scale(Size::new(4000, 3000), Some(1.0), &options), 1.0);
+    }
+
+    #[test]
+    fn detector_requires_both_super_resolution_files() {
+        let error = |options: DetectorOptions| create_detector(&options).err().map(|e| e.message);
+        let only_prototxt = DetectorOptions {
+            sr_prototxt: Some("sr.prototxt".to_string()),
+            ..DetectorOptions::default()
+        };
+        assert_eq!(error(only_prototxt), Some("Both sr_prototxt and sr_model must be set".to_string()));
+        let missing = DetectorOptions {
+            sr_prototxt: Some("/nonexistent/sr.prototxt".to_string()),
+            sr_model: Some("/nonexistent/sr.caffemodel".to_string()),
+            ..DetectorOptions::default()
+        };
+        assert_eq!(error(missing), Some("Super resolution model file not found: /nonexistent/sr.prototxt".to_string()));
+    }
+}
@@ -0,0 +1,82 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, BIG5, EUC_KR, GBK, SHIFT_JIS, UTF_16BE, UTF_8, WINDOWS_1252};
+
+// 解码器在没有 ECI 时可能采用的字符集，用于判断解码器给出的文本是否完全由字节段解码而来
+const KNOWN_ENCODINGS: [&Encoding; 6] = [WINDOWS_1252, SHIFT_JIS, GBK, BIG5, EUC_KR, UTF_16BE];
+
+pub(crate) struct DecodedText {
+    // 无法无损解码为文本时为 None
+    pub(crate) text: Option<String>,
+    pub(crate) charset: Option<&'static str>,
+}
+
+// ISO-8859-1 与 Unicode 前 256 个码位一一对应，encoding_rs 将其视为 windows-1252，这里单独处理
+fn decode_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+// 严格解码，遇到非法字节序列时返回 None，保证结果可以无损还原为原始字节
+fn decode_strict(bytes: &[u8], encoding: &'static Encoding) -> Option<String> {
+    encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .map(|text| text.into_owned())
+}
+
+// 除 GS1 的 FNC1（GS）、AAMVA 使用的 RS/FS/EOT 及常见空白外，出现控制字符即视为二进制数据
+pub(crate) fn is_plain_text(text: &str) -> bool {
+    text.chars().all(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r' | '\u{04}' | '\u{1C}' | '\u{1D}' | '\u{1E}'))
+}
+
+// 没有 ECI 指定字符集时猜测字节的编码：先严格按 UTF-8 解码，
+// 失败时由 chardetng 判断，再依次尝试供应商标签常见的 GBK 与 Shift_JIS
+pub(crate) fn detect_text(bytes: &[u8]) -> DecodedText {
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        if is_plain_text(text) {
+            return DecodedText {
+                text: Some(text.to_string()),
+                charset: Some(UTF_8.name()),
+            };
+        }
+    }
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    let guess = detector.guess(None, false);
+    let mut candidates = vec![guess];
+    for encoding in [GBK, SHIFT_JIS] {
+        if !candidates.contains(&encoding) {
+            candidates.push(encoding);
+        }
+    }
+    for encoding in candidates {
+        if let Some(text) = decode_strict(bytes, encoding).filter(|t| is_plain_text(t)) {
+            return DecodedText {
+                text: Some(text),
+                charset: Some(encoding.name()),
+            };
+        }
+    }
+    DecodedText { text: None, charset: None }
+}
+
+// 找出能将 bytes 解码为 text 的字符集，用于确认解码器给出的文本与字节段完全对应
+pub(crate) fn identify_charset(bytes: &[u8], text: &str) -> Option<&'static str> {
+    if std::str::from_utf8(bytes) == Ok(text) {
+        return Some(UTF_8.name());
+    }
+    if decode_latin1(bytes) == text {
+        return Some("ISO-8859-1");
+    }
+    KNOWN_ENCODINGS
+        .iter()
+        .find(|encoding| decode_strict(bytes, encoding).is_some_and(|decoded| decoded == text))
+        .map(|encoding| encoding.name())
+}
+
+// 按已知字符集（如 ECI 指定的字符集）严格解码，字符集名称取 identify_charset 的返回值
+pub(crate) fn decode_with(bytes: &[u8], charset: &str) -> Option<String> {
+    if charset == "ISO-8859-1" {
+        return Some(decode_latin1(bytes));
+    }
+    decode_strict(bytes, Encoding::for_label(charset.as_bytes())?)
+}
@@ -0,0 +1,584 @@
+use std::collections::BTreeMap;
+use crate::service::aamva::parse_aamva;
+use crate::service::dto::Content;
+use crate::service::gs1::percent_decode;
+
+// 按未转义的分隔符切分，并去掉 \ 转义（WiFi、MeCard 使用 \; \, \: \\）
+fn split_escaped(text: &str, separator: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            current.extend(chars.next());
+        } else if c == separator {
+            fields.push(std::mem::take(&mut current));
+        } else {
+            current.push(c);
+        }
+    }
+    fields.push(current);
+    fields
+}
+
+// WiFi、MeCard、MATMSG 的 KEY:value;KEY:value;; 形式，同一个键可出现多次
+fn parse_fields(body: &str) -> Vec<(String, String)> {
+    split_escaped(body, ';')
+        .into_iter()
+        .filter_map(|field| {
+            let (key, value) = field.split_once(':')?;
+            Some((key.trim().to_ascii_uppercase(), value.to_string()))
+        })
+        .collect()
+}
+
+fn first(fields: &[(String, String)], key: &str) -> Option<String> {
+    fields.iter().find(|(k, v)| k == key && !v.is_empty()).map(|(_, v)| v.clone())
+}
+
+fn all(fields: &[(String, String)], key: &str) -> Vec<String> {
+    fields.iter().filter(|(k, v)| k == key && !v.is_empty()).map(|(_, v)| v.clone()).collect()
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = text.get(..prefix.len())?;
+    if head.eq_ignore_ascii_case(prefix) {
+        Some(&text[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+// WIFI:T:WPA;S:mynetwork;P:mypass;H:true;;
+fn parse_wifi(body: &str) -> Option<Content> {
+    let fields = parse_fields(body);
+    Some(Content::Wifi {
+        ssid: first(&fields, "S")?,
+        security: first(&fields, "T"),
+        password: first(&fields, "P"),
+        hidden: first(&fields, "H").is_some_and(|h| h.eq_ignore_ascii_case("true")),
+    })
+}
+
+// MECARD:N:Owen,Sean;TEL:+12125551212;EMAIL:a@example.com;;，N 为“姓,名”
+fn parse_mecard(body: &str) -> Option<Content> {
+    let fields = parse_fields(body);
+    let name = first(&fields, "N").map(|n| match n.split_once(',') {
+        Some((last, given)) => format!("{} {}", given.trim(), last.trim()),
+        None => n,
+    });
+    Some(Content::Contact {
+        format: "mecard",
+        name,
+        organization: first(&fields, "ORG"),
+        title: None,
+        phones: all(&fields, "TEL"),
+        emails: all(&fields, "EMAIL"),
+        urls: all(&fields, "URL"),
+        addresses: all(&fields, "ADR"),
+        note: first(&fields, "NOTE"),
+    })
+}
+
+// vCard/iCalendar 的内容行：续行以空格或制表符开头，属性名后可带 ;TYPE=... 参数
+fn content_lines(text: &str) -> Vec<(String, String)> {
+    let mut unfolded = Vec::<String>::new();
+    for line in text.lines() {
+        match (line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')), unfolded.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => unfolded.push(line.to_string()),
+        }
+    }
+    unfolded
+        .into_iter()
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            let name = name.split(';').next().unwrap_or_default();
+            // 去掉分组前缀，如 item1.EMAIL
+            let name = name.rsplit('.').next().unwrap_or_default().to_ascii_uppercase();
+            Some((name, value.trim_end_matches('\r').to_string()))
+        })
+        .collect()
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => text.push('\n'),
+                Some(other) => text.push(other),
+                None => {}
+            }
+        } else {
+            text.push(c);
+        }
+    }
+    text
+}
+
+fn parse_vcard(text: &str) -> Option<Content> {
+    let lines = content_lines(text)
+        .into_iter()
+        .map(|(name, value)| (name, unescape_text(&value)))
+        .collect::<Vec<(String, String)>>();
+    // N 为“姓;名;中间名;前缀;后缀”，没有 FN 时由 N 拼出
+    let name = first(&lines, "FN").or_else(|| {
+        first(&lines, "N").map(|n| {
+            let parts = n.split(';').collect::<Vec<&str>>();
+            let mut ordered = parts.iter().skip(1).take(2).copied().collect::<Vec<&str>>();
+            ordered.extend(parts.first());
+            ordered.into_iter().filter(|p| !p.is_empty()).collect::<Vec<&str>>().join(" ")
+        })
+    });
+    // ADR 的各组成部分以分号分隔，拼接非空部分
+    let addresses = all(&lines, "ADR")
+        .into_iter()
+        .map(|a| a.split(';').map(str::trim).filter(|p| !p.is_empty()).collect::<Vec<&str>>().join(", "))
+        .collect();
+    Some(Content::Contact {
+        format: "vcard",
+        name,
+        organization: first(&lines, "ORG").map(|o| o.replace(';', ", ")),
+        title: first(&lines, "TITLE"),
+        phones: all(&lines, "TEL").into_iter().map(|t| t.trim_start_matches("tel:").to_string()).collect(),
+        emails: all(&lines, "EMAIL"),
+        urls: all(&lines, "URL"),
+        addresses,
+        note: first(&lines, "NOTE"),
+    })
+}
+
+// 20240101T093000Z / 20240101 转为 2024-01-01T09:30:00Z / 2024-01-01
+fn ical_datetime(value: &str) -> String {
+    let date = value.get(..8).filter(|d| d.bytes().all(|b| b.is_ascii_digit()));
+    let time = value.get(9..15).filter(|t| value.as_bytes()[8] == b'T' && t.bytes().all(|b| b.is_ascii_digit()));
+    match (date, time) {
+        (Some(d), Some(t)) => format!("{}-{}-{}T{}:{}:{}{}", &d[..4], &d[4..6], &d[6..], &t[..2], &t[2..4], &t[4..], &value[15..]),
+        (Some(d), None) if value.len() == 8 => format!("{}-{}-{}", &d[..4], &d[4..6], &d[6..]),
+        _ => value.to_string(),
+    }
+}
+
+fn parse_event(text: &str) -> Option<Content> {
+    let lines = content_lines(text);
+    // 只取第一个 VEVENT 中的属性
+    let start = lines.iter().position(|(k, v)| k == "BEGIN" && v.eq_ignore_ascii_case("VEVENT"))?;
+    let end = lines.iter().skip(start).position(|(k, v)| k == "END" && v.eq_ignore_ascii_case("VEVENT")).map(|i| start + i).unwrap_or(lines.len());
+    let event = lines[start..end]
+        .iter()
+        .map(|(name, value)| (name.clone(), unescape_text(value)))
+        .collect::<Vec<(String, String)>>();
+    Some(Content::Event {
+        summary: first(&event, "SUMMARY"),
+        start: first(&event, "DTSTART").map(|v| ical_datetime(&v)),
+        end: first(&event, "DTEND").map(|v| ical_datetime(&v)),
+        location: first(&event, "LOCATION"),
+        description: first(&event, "DESCRIPTION"),
+    })
+}
+
+// geo:39.9087,116.3975[,alt][;crs=...][?q=天安门]
+fn parse_geo(body: &str) -> Option<Content> {
+    let (coordinates, query) = match body.split_once('?') {
+        Some((c, q)) => (c, Some(q)),
+        None => (body, None),
+    };
+    let coordinates = coordinates.split(';').next()?;
+    let values = coordinates.split(',').map(|v| v.trim().parse::<f64>().ok()).collect::<Option<Vec<f64>>>()?;
+    let (latitude, longitude) = (*values.first()?, *values.get(1)?);
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return None;
+    }
+    let query = query
+        .into_iter()
+        .flat_map(|q| q.split('&'))
+        .find_map(|p| p.strip_prefix("q="))
+        .and_then(|q| percent_decode(&q.replace('+', " ")).ok());
+    Some(Content::Geo {
+        latitude,
+        longitude,
+        altitude: values.get(2).copied(),
+        query,
+    })
+}
+
+fn split_addresses(value: &str) -> Vec<String> {
+    value.split(',').map(|a| a.trim().to_string()).filter(|a| !a.is_empty()).collect()
+}
+
+// mailto:a@example.com,b@example.com?cc=c@example.com&subject=Hi&body=...
+fn parse_mailto(body: &str) -> Option<Content> {
+    let decode = |v: &str| percent_decode(v).unwrap_or_else(|_| v.to_string());
+    let (to, query) = match body.split_once('?') {
+        Some((to, q)) => (to, q),
+        None => (body, ""),
+    };
+    let (mut cc, mut bcc, mut subject, mut mail_body) = (Vec::new(), Vec::new(), None, None);
+    let mut to = split_addresses(&decode(to));
+    for param in query.split('&') {
+        let (key, value) = param.split_once('=').unwrap_or((param, ""));
+        let value = decode(value);
+        match key.to_ascii_lowercase().as_str() {
+            "to" => to.extend(split_addresses(&value)),
+            "cc" => cc.extend(split_addresses(&value)),
+            "bcc" => bcc.extend(split_addresses(&value)),
+            "subject" => subject = Some(value),
+            "body" => mail_body = Some(value),
+            _ => {}
+        }
+    }
+    Some(Content::Email {
+        to,
+        cc,
+        bcc,
+        subject,
+        body: mail_body,
+    })
+}
+
+// MATMSG:TO:a@example.com;SUB:Hi;BODY:...;;
+fn parse_matmsg(body: &str) -> Option<Content> {
+    let fields = parse_fields(body);
+    Some(Content::Email {
+        to: all(&fields, "TO"),
+        cc: Vec::new(),
+        bcc: Vec::new(),
+        subject: first(&fields, "SUB"),
+        body: first(&fields, "BODY"),
+    })
+}
+
+// sms:+8613800000000?body=... 或 SMSTO:+8613800000000:...
+fn parse_sms(body: &str, smsto: bool) -> Option<Content> {
+    let (number, message) = if smsto {
+        match body.split_once(':') {
+            Some((n, m)) => (n.to_string(), Some(m.to_string())),
+            None => (body.to_string(), None),
+        }
+    } else {
+        match body.split_once('?') {
+            Some((n, q)) => (n.to_string(), q.split('&').find_map(|p| p.strip_prefix("body=")).map(|b| percent_decode(b).unwrap_or_else(|_| b.to_string()))),
+            None => (body.to_string(), None),
+        }
+    };
+    if number.is_empty() {
+        return None;
+    }
+    Some(Content::Sms { number, body: message })
+}
+
+// EMV 的 TLV：2 位数字 ID + 2 位数字长度 + 值，长度按字符计
+fn parse_tlv(data: &str) -> Option<Vec<(String, String)>> {
+    let chars = data.chars().collect::<Vec<char>>();
+    let mut items = Vec::new();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let id = chars.get(pos..pos + 2)?.iter().collect::<String>();
+        let length = chars.get(pos + 2..pos + 4)?.iter().collect::<String>().parse::<usize>().ok()?;
+        let value = chars.get(pos + 4..pos + 4 + length)?.iter().collect::<String>();
+        if !id.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        items.push((id, value));
+        pos += 4 + length;
+    }
+    Some(items)
+}
+
+// CRC-16/CCITT-FALSE（多项式 0x1021，初值 0xFFFF）
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+fn parse_emv(text: &str) -> Option<Content> {
+    let items = parse_tlv(text)?;
+    let value = |id: &str| items.iter().find(|(k, _)| k == id).map(|(_, v)| v.clone());
+    // CRC 覆盖从开头到 ID 63 及其长度 "6304" 为止的全部内容
+    let crc_valid = match (value("63"), text.rfind("6304")) {
+        (Some(crc), Some(pos)) if pos + 8 == text.len() => u16::from_str_radix(&crc, 16).ok() == Some(crc16(&text.as_bytes()[..pos + 4])),
+        _ => false,
+    };
+    let merchant_accounts = items
+        .iter()
+        .filter(|(id, _)| id.parse::<u8>().is_ok_and(|n| (2..=51).contains(&n)))
+        .map(|(id, v)| {
+            let guid = parse_tlv(v).and_then(|sub| sub.into_iter().find(|(k, _)| k == "00").map(|(_, g)| g));
+            (id.clone(), guid.unwrap_or_else(|| v.clone()))
+        })
+        .collect::<BTreeMap<String, String>>();
+    Some(Content::EmvPayment {
+        point_of_initiation: value("01"),
+        merchant_accounts,
+        merchant_category_code: value("52"),
+        currency: value("53"),
+        amount: value("54"),
+        country: value("58"),
+        merchant_name: value("59"),
+        merchant_city: value("60"),
+        postal_code: value("61"),
+        crc_valid,
+    })
+}
+
+// IBAN 校验：前 4 位移到末尾，字母转为 10~35，整体模 97 余 1
+fn iban_valid(iban: &str) -> bool {
+    let iban = iban.replace(' ', "");
+    if iban.len() < 15 || !iban.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return false;
+    }
+    let rearranged = format!("{}{}", &iban[4..], &iban[..4]);
+    let mut remainder = 0u32;
+    for c in rearranged.chars() {
+        let value = c.to_digit(36).unwrap_or(0);
+        remainder = if value >= 10 { (remainder * 100 + value) % 97 } else { (remainder * 10 + value) % 97 };
+    }
+    remainder == 1
+}
+
+// BCD\n002\n1\nSCT\nBIC\n收款人\nIBAN\nEUR12.30\n用途\n结构化附言\n非结构化附言
+fn parse_epc(text: &str) -> Option<Content> {
+    let lines = text.lines().map(|l| l.trim_end_matches('\r')).collect::<Vec<&str>>();
+    if lines.len() < 7 || lines[3] != "SCT" {
+        return None;
+    }
+    let optional = |i: usize| lines.get(i).map(|v| v.trim()).filter(|v| !v.is_empty()).map(str::to_string);
+    let (currency, amount) = match optional(7) {
+        Some(a) if a.len() > 3 && a.is_char_boundary(3) => (Some(a[..3].to_string()), Some(a[3..].to_string())),
+        _ => (None, None),
+    };
+    let iban = lines[6].trim().to_string();
+    Some(Content::EpcPayment {
+        version: lines[1].to_string(),
+        bic: optional(4),
+        name: lines[5].trim().to_string(),
+        iban_valid: iban_valid(&iban),
+        iban,
+        currency,
+        amount,
+        purpose: optional(8),
+        reference: optional(9),
+        text: optional(10),
+    })
+}
+
+// 按前缀识别内容类型并解析，无法识别或格式不完整时返回 None
+pub fn parse_content(text: &str) -> Option<Content> {
+    // AAMVA 以 @ LF RS CR 开头，控制字符是格式的一部分，不能先去掉首尾空白
+    if text.starts_with('@') {
+        return parse_aamva(text).ok().map(|license| Content::DriverLicense(Box::new(license)));
+    }
+    let trimmed = text.trim();
+    if let Some(body) = strip_prefix_ignore_case(trimmed, "WIFI:") {
+        return parse_wifi(body);
+    }
+    if let Some(body) = strip_prefix_ignore_case(trimmed, "MECARD:") {
+        return parse_mecard(body);
+    }
+    if strip_prefix_ignore_case(trimmed, "BEGIN:VCARD").is_some() {
+        return parse_vcard(trimmed);
+    }
+    if strip_prefix_ignore_case(trimmed, "BEGIN:VCALENDAR").is_some() || strip_prefix_ignore_case(trimmed, "BEGIN:VEVENT").is_some() {
+        return parse_event(trimmed);
+    }
+    if let Some(body) = strip_prefix_ignore_case(trimmed, "geo:") {
+        return parse_geo(body);
+    }
+    if let Some(body) = strip_prefix_ignore_case(trimmed, "mailto:") {
+        return parse_mailto(body);
+    }
+    if let Some(body) = strip_prefix_ignore_case(trimmed, "MATMSG:") {
+        return parse_matmsg(body);
+    }
+    if let Some(body) = strip_prefix_ignore_case(trimmed, "SMSTO:") {
+        return parse_sms(body, true);
+    }
+    if let Some(body) = strip_prefix_ignore_case(trimmed, "sms:") {
+        return parse_sms(body, false);
+    }
+    if let Some(body) = strip_prefix_ignore_case(trimmed, "tel:") {
+        return Some(Content::Phone { number: body.to_string() });
+    }
+    if trimmed.starts_with("BCD\n") || trimmed.starts_with("BCD\r\n") {
+        return parse_epc(trimmed);
+    }
+    // EMV 负载以 ID 00、长度 02、值 01 开头
+    if trimmed.starts_with("000201") {
+        return parse_emv(trimmed);
+    }
+    if strip_prefix_ignore_case(trimmed, "http://").is_some() || strip_prefix_ignore_case(trimmed, "https://").is_some() {
+        return Some(Content::Url { url: trimmed.to_string() });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_wifi_with_escapes() {
+        assert_eq!(
+            parse_content("WIFI:T:WPA;S:my\\;net;P:pa\\:ss;H:true;;"),
+            Some(Content::Wifi {
+                ssid: "my;net".to_string(),
+                security: Some("WPA".to_string()),
+                password: Some("pa:ss".to_string()),
+                hidden: true,
+            })
+        );
+        // 缺少 SSID 时不是有效的 WiFi 配置
+        assert_eq!(parse_content("WIFI:T:WPA;P:pass;;"), None);
+    }
+
+    #[test]
+    fn parses_mecard_and_vcard() {
+        let Some(Content::Contact { format, name, phones, emails, .. }) = parse_content("MECARD:N:Owen,Sean;TEL:+12125551212;TEL:+12125551213;EMAIL:srowen@example.com;;") else {
+            panic!("expected contact");
+        };
+        assert_eq!(format, "mecard");
+        assert_eq!(name.as_deref(), Some("Sean Owen"));
+        assert_eq!(phones, ["+12125551212", "+12125551213"]);
+        assert_eq!(emails, ["srowen@example.com"]);
+
+        let vcard = "BEGIN:VCARD\r\nVERSION:3.0\r\nN:Doe;John;;;\r\nORG:Example;Sales\r\nTEL;TYPE=cell:+1 555 0100\r\nitem1.EMAIL:john@exa\r\n mple.com\r\nADR:;;1 Main St;Springfield;;12345;US\r\nNOTE:line1\\nline2\r\nEND:VCARD";
+        let Some(Content::Contact { format, name, organization, phones, emails, addresses, note, .. }) = parse_content(vcard) else {
+            panic!("expected contact");
+        };
+        assert_eq!(format, "vcard");
+        assert_eq!(name.as_deref(), Some("John Doe"));
+        assert_eq!(organization.as_deref(), Some("Example, Sales"));
+        assert_eq!(phones, ["+1 555 0100"]);
+        // 续行拼接、分组前缀去掉
+        assert_eq!(emails, ["john@example.com"]);
+        assert_eq!(addresses, ["1 Main St, Springfield, 12345, US"]);
+        assert_eq!(note.as_deref(), Some("line1\nline2"));
+    }
+
+    #[test]
+    fn parses_event() {
+        let text = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:Review\nDTSTART:20240101T093000Z\nDTEND:20240102\nLOCATION:Room 1\\, East\nEND:VEVENT\nEND:VCALENDAR";
+        assert_eq!(
+            parse_content(text),
+            Some(Content::Event {
+                summary: Some("Review".to_string()),
+                start: Some("2024-01-01T09:30:00Z".to_string()),
+                end: Some("2024-01-02".to_string()),
+                location: Some("Room 1, East".to_string()),
+                description: None,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_geo() {
+        assert_eq!(
+            parse_content("geo:39.9087,116.3975,50;crs=wgs84?q=Tian%27anmen+Square"),
+            Some(Content::Geo {
+                latitude: 39.9087,
+                longitude: 116.3975,
+                altitude: Some(50.0),
+                query: Some("Tian'anmen Square".to_string()),
+            })
+        );
+        assert_eq!(parse_content("geo:91,0"), None);
+        assert_eq!(parse_content("geo:abc,0"), None);
+    }
+
+    #[test]
+    fn parses_mailto_and_matmsg() {
+        assert_eq!(
+            parse_content("mailto:a@example.com,b@example.com?cc=c@example.com&subject=Hello%20there&body=Hi"),
+            Some(Content::Email {
+                to: vec!["a@example.com".to_string(), "b@example.com".to_string()],
+                cc: vec!["c@example.com".to_string()],
+                bcc: Vec::new(),
+                subject: Some("Hello there".to_string()),
+                body: Some("Hi".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_content("MATMSG:TO:a@example.com;SUB:Hi;BODY:See you;;"),
+            Some(Content::Email {
+                to: vec!["a@example.com".to_string()],
+                cc: Vec::new(),
+                bcc: Vec::new(),
+                subject: Some("Hi".to_string()),
+                body: Some("See you".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn parses_sms_and_phone() {
+        let sms = |number: &str, body: Option<&str>| Some(Content::Sms {
+            number: number.to_string(),
+            body: body.map(str::to_string),
+        });
+        assert_eq!(parse_content("sms:+8613800000000?body=Hello%20world"), sms("+8613800000000", Some("Hello world")));
+        assert_eq!(parse_content("SMSTO:+8613800000000:Hello: world"), sms("+8613800000000", Some("Hello: world")));
+        assert_eq!(parse_content("sms:"), None);
+        assert_eq!(parse_content("tel:+8613800000000"), Some(Content::Phone { number: "+8613800000000".to_string() }));
+    }
+
+    #[test]
+    fn crc16_matches_ccitt_false_check_value() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn parses_emv_merchant_presented_payload() {
+        let payload = "00020101021226330015com.example.pay01101234567890520458125303840540512.505802US5909BEST CAFE6008NEW YORK6105100016304C9DB";
+        let Some(Content::EmvPayment { point_of_initiation, merchant_accounts, merchant_category_code, currency, amount, country, merchant_name, merchant_city, postal_code, crc_valid }) = parse_content(payload) else {
+            panic!("expected EMV payment");
+        };
+        assert_eq!(point_of_initiation.as_deref(), Some("12"));
+        assert_eq!(merchant_accounts.get("26").map(String::as_str), Some("com.example.pay"));
+        assert_eq!(merchant_category_code.as_deref(), Some("5812"));
+        assert_eq!((currency.as_deref(), amount.as_deref(), country.as_deref()), (Some("840"), Some("12.50"), Some("US")));
+        assert_eq!((merchant_name.as_deref(), merchant_city.as_deref(), postal_code.as_deref()), (Some("BEST CAFE"), Some("NEW YORK"), Some("10001")));
+        assert!(crc_valid);
+
+        // 篡改金额后 CRC 不再匹配
+        let tampered = payload.replace("12.50", "92.50");
+        assert!(matches!(parse_content(&tampered), Some(Content::EmvPayment { crc_valid: false, .. })));
+    }
+
+    #[test]
+    fn parses_epc_payment() {
+        let text = "BCD\n002\n1\nSCT\nCOBADEFFXXX\nMax Mustermann\nDE89 3704 0044 0532 0130 00\nEUR12.30\nGDDS\n\nInvoice 42";
+        assert_eq!(
+            parse_content(text),
+            Some(Content::EpcPayment {
+                version: "002".to_string(),
+                bic: Some("COBADEFFXXX".to_string()),
+                name: "Max Mustermann".to_string(),
+                iban: "DE89 3704 0044 0532 0130 00".to_string(),
+                iban_valid: true,
+                currency: Some("EUR".to_string()),
+                amount: Some("12.30".to_string()),
+                purpose: Some("GDDS".to_string()),
+                reference: None,
+                text: Some("Invoice 42".to_string()),
+            })
+        );
+        assert!(iban_valid("DE89370400440532013000"));
+        assert!(!iban_valid("DE89370400440532013001"));
+        // 只支持 SCT
+        assert_eq!(parse_content("BCD\n002\n1\nINST\n\nMax\nDE89370400440532013000"), None);
+    }
+
+    #[test]
+    fn recognizes_urls_and_ignores_plain_text() {
+        assert_eq!(parse_content(" https://example.com/a?b=c "), Some(Content::Url { url: "https://example.com/a?b=c".to_string() }));
+        assert_eq!(parse_content("hello world"), None);
+    }
+}
@@ -0,0 +1,300 @@
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::{DecodeError, Engine};
+use std::fmt;
+
+// 解析 data URI（RFC 2397）或裸 base64 图片数据。
+// 兼容标准与 URL-safe 字母表、有无填充以及夹杂的空白和换行（如 MIME 每 76 字符折行）
+const STANDARD_LENIENT: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, lenient_config());
+const URL_SAFE_LENIENT: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, lenient_config());
+
+const fn lenient_config() -> GeneralPurposeConfig {
+    GeneralPurposeConfig::new()
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent)
+        .with_decode_allow_trailing_bits(true)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataUriError {
+    Empty,
+    // 头部不符合 data:[<mediatype>][;param=value][;base64], 语法
+    MalformedHeader(String),
+    // 声明的媒体类型不是图片
+    UnsupportedMediaType(String),
+    // position 为字符在数据部分（逗号之后）中的字节偏移
+    InvalidCharacter { position: usize, character: char },
+    // 去掉空白与填充后的有效字符数不能构成完整的 base64 分组
+    InvalidLength(usize),
+    InvalidPadding,
+    InvalidPercentEncoding { position: usize },
+    // 声明的 MIME 与按文件头识别出的格式不一致
+    MimeMismatch { declared: String, detected: Option<&'static str> },
+}
+
+impl fmt::Display for DataUriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataUriError::Empty => write!(f, "Image data is empty"),
+            DataUriError::MalformedHeader(reason) => write!(f, "Malformed data URI header: {}", reason),
+            DataUriError::UnsupportedMediaType(mime) => write!(f, "Unsupported media type {:?}, expected image/*", mime),
+            DataUriError::InvalidCharacter { position, character } => {
+                write!(f, "Invalid base64 character {:?} at position {}", character, position)
+            }
+            DataUriError::InvalidLength(symbols) => write!(f, "Invalid base64 length: {} symbols cannot end a complete group", symbols),
+            DataUriError::InvalidPadding => write!(f, "Invalid base64 padding"),
+            DataUriError::InvalidPercentEncoding { position } => write!(f, "Invalid percent encoding at position {}", position),
+            DataUriError::MimeMismatch { declared, detected } => match detected {
+                Some(detected) => write!(f, "Declared media type {} does not match image data ({})", declared, detected),
+                None => write!(f, "Declared media type {} does not match image data (unknown format)", declared),
+            },
+        }
+    }
+}
+
+impl std::error::Error for DataUriError {}
+
+// 解码 data URI 或裸 base64 字符串为图片字节。声明了常见图片类型时校验文件头是否一致
+pub fn decode_image_data(input: &str) -> Result<Vec<u8>, DataUriError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(DataUriError::Empty);
+    }
+    let Some(rest) = strip_prefix_ignore_case(input, "data:") else {
+        if input.contains(',') {
+            return Err(DataUriError::MalformedHeader("header before ',' must start with \"data:\"".to_string()));
+        }
+        return decode_base64(input);
+    };
+    let (header, payload) = rest
+        .split_once(',')
+        .ok_or_else(|| DataUriError::MalformedHeader("missing ',' between header and data".to_string()))?;
+
+    let mut params = header.split(';');
+    let media_type = params.next().unwrap_or_default().trim().to_ascii_lowercase();
+    let mut is_base64 = false;
+    for param in params {
+        let param = param.trim();
+        if param.eq_ignore_ascii_case("base64") {
+            is_base64 = true;
+        } else if is_base64 || !param.contains('=') {
+            // base64 只能是最后一个参数，其余参数必须是 key=value
+            return Err(DataUriError::MalformedHeader(format!("invalid parameter {:?}", param)));
+        }
+    }
+
+    let declared = if media_type.is_empty() {
+        None
+    } else {
+        let (kind, subtype) = media_type
+            .split_once('/')
+            .ok_or_else(|| DataUriError::MalformedHeader(format!("invalid media type {:?}", media_type)))?;
+        if kind != "image" || subtype.is_empty() {
+            return Err(DataUriError::UnsupportedMediaType(media_type));
+        }
+        Some(canonical_mime(&media_type))
+    };
+
+    let data = if is_base64 { decode_base64(payload)? } else { percent_decode(payload)? };
+    if data.is_empty() {
+        return Err(DataUriError::Empty);
+    }
+
+    // 只校验能按文件头识别的格式，其余 image/* 交给后续解码判断
+    if let Some(declared) = declared {
+        if KNOWN_MIMES.contains(&declared.as_str()) {
+            let detected = sniff_image_mime(&data);
+            if detected != Some(declared.as_str()) {
+                return Err(DataUriError::MimeMismatch { declared, detected });
+            }
+        }
+    }
+    Ok(data)
+}
+
+const KNOWN_MIMES: [&str; 6] = ["image/png", "image/jpeg", "image/gif", "image/bmp", "image/webp", "image/tiff"];
+
+// 按文件头魔数识别常见图片格式
+pub fn sniff_image_mime(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if data.starts_with(b"BM") {
+        Some("image/bmp")
+    } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        Some("image/tiff")
+    } else {
+        None
+    }
+}
+
+// 常见的非标准别名
+fn canonical_mime(mime: &str) -> String {
+    match mime {
+        "image/jpg" | "image/pjpeg" => "image/jpeg",
+        "image/x-png" => "image/png",
+        "image/x-bmp" | "image/x-ms-bmp" => "image/bmp",
+        "image/tif" => "image/tiff",
+        other => other,
+    }
+    .to_string()
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = s.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix).then(|| &s[prefix.len()..])
+}
+
+fn decode_base64(payload: &str) -> Result<Vec<u8>, DataUriError> {
+    let symbols: Vec<u8> = payload.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if symbols.is_empty() {
+        return Err(DataUriError::Empty);
+    }
+
+    // 两种字母表混用视为错误，指向第一个与已出现字母表冲突的字符
+    let standard = symbols.iter().position(|b| matches!(b, b'+' | b'/'));
+    let url_safe = symbols.iter().position(|b| matches!(b, b'-' | b'_'));
+    let engine = match (standard, url_safe) {
+        (Some(s), Some(u)) => {
+            let offset = s.max(u);
+            return Err(invalid_character(payload, offset, symbols[offset]));
+        }
+        (None, Some(_)) => &URL_SAFE_LENIENT,
+        _ => &STANDARD_LENIENT,
+    };
+
+    engine.decode(&symbols).map_err(|e| match e {
+        DecodeError::InvalidByte(offset, byte) => invalid_character(payload, offset, byte),
+        DecodeError::InvalidLastSymbol(offset, byte) => invalid_character(payload, offset, byte),
+        DecodeError::InvalidLength(_) => DataUriError::InvalidLength(symbols.iter().filter(|&&b| b != b'=').count()),
+        DecodeError::InvalidPadding => DataUriError::InvalidPadding,
+    })
+}
+
+// 将去掉空白后的偏移换算回原始数据中的位置，便于定位折行数据中的错误
+fn invalid_character(payload: &str, offset: usize, byte: u8) -> DataUriError {
+    let position = payload
+        .bytes()
+        .enumerate()
+        .filter(|(_, b)| !b.is_ascii_whitespace())
+        .nth(offset)
+        .map(|(i, _)| i)
+        .unwrap_or(offset);
+    let character = payload.get(position..).and_then(|s| s.chars().next()).unwrap_or(byte as char);
+    DataUriError::InvalidCharacter { position, character }
+}
+
+fn percent_decode(payload: &str) -> Result<Vec<u8>, DataUriError> {
+    let bytes = payload.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            // from_str_radix 接受前导的 '+'，先确认是两个十六进制数字
+            let hex = bytes
+                .get(i + 1..i + 3)
+                .filter(|h| h.iter().all(u8::is_ascii_hexdigit))
+                .map(|h| (hex_value(h[0]) << 4) | hex_value(h[1]));
+            decoded.push(hex.ok_or(DataUriError::InvalidPercentEncoding { position: i })?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(decoded)
+}
+
+fn hex_value(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        _ => digit - b'A' + 10,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+
+    // 含有 '+' '/' 或 '-' '_' 的编码结果，便于测试两种字母表
+    const GIF: &[u8] = b"GIF89a\x01\x00\x01\x00\xfb\xff\xfe\x00";
+
+    #[test]
+    fn decodes_data_uri_and_bare_base64() {
+        let encoded = STANDARD.encode(GIF);
+        assert_eq!(decode_image_data(&format!("data:image/gif;base64,{}", encoded)).unwrap(), GIF);
+        assert_eq!(decode_image_data(&format!("DATA:Image/GIF;name=a.gif;BASE64,{}", encoded)).unwrap(), GIF);
+        assert_eq!(decode_image_data(&format!("data:;base64,{}", encoded)).unwrap(), GIF);
+        assert_eq!(decode_image_data(&encoded).unwrap(), GIF);
+    }
+
+    #[test]
+    fn accepts_url_safe_unpadded_and_wrapped_base64() {
+        let url_safe = URL_SAFE_NO_PAD.encode(GIF);
+        assert!(url_safe.contains('-') || url_safe.contains('_'));
+        assert_eq!(decode_image_data(&url_safe).unwrap(), GIF);
+
+        let standard = STANDARD.encode(GIF);
+        let wrapped = format!("data:image/gif;base64,\r\n{}\n {}\n", &standard[..8], &standard[8..]);
+        assert_eq!(decode_image_data(&wrapped).unwrap(), GIF);
+    }
+
+    #[test]
+    fn decodes_percent_encoded_data() {
+        assert_eq!(decode_image_data("data:image/gif,GIF89a%01%00").unwrap(), b"GIF89a\x01\x00");
+        assert_eq!(
+            decode_image_data("data:image/gif,GIF89a%0"),
+            Err(DataUriError::InvalidPercentEncoding { position: 6 })
+        );
+        assert_eq!(
+            decode_image_data("data:image/gif,GIF89a%+1"),
+            Err(DataUriError::InvalidPercentEncoding { position: 6 })
+        );
+        assert_eq!(decode_image_data("data:image/gif,GIF89a%fF").unwrap(), b"GIF89a\xff");
+    }
+
+    #[test]
+    fn reports_invalid_character_position_in_original_data() {
+        assert_eq!(
+            decode_image_data("data:image/gif;base64,R0lG\nOD*h"),
+            Err(DataUriError::InvalidCharacter { position: 7, character: '*' })
+        );
+        assert_eq!(
+            decode_image_data("R0lG+/-_"),
+            Err(DataUriError::InvalidCharacter { position: 6, character: '-' })
+        );
+        assert_eq!(decode_image_data("R0lGO"), Err(DataUriError::InvalidLength(5)));
+    }
+
+    #[test]
+    fn validates_header_and_media_type() {
+        assert!(matches!(decode_image_data("image/png;base64,AAAA"), Err(DataUriError::MalformedHeader(_))));
+        assert!(matches!(decode_image_data("data:image/png;base64"), Err(DataUriError::MalformedHeader(_))));
+        assert!(matches!(decode_image_data("data:image/png;base64;x=1,AAAA"), Err(DataUriError::MalformedHeader(_))));
+        assert_eq!(
+            decode_image_data("data:text/plain;base64,AAAA"),
+            Err(DataUriError::UnsupportedMediaType("text/plain".to_string()))
+        );
+        assert_eq!(decode_image_data("data:image/png;base64,"), Err(DataUriError::Empty));
+    }
+
+    #[test]
+    fn rejects_mime_mismatch() {
+        let encoded = STANDARD.encode(GIF);
+        assert_eq!(
+            decode_image_data(&format!("data:image/png;base64,{}", encoded)),
+            Err(DataUriError::MimeMismatch { declared: "image/png".to_string(), detected: Some("image/gif") })
+        );
+        let jpeg = STANDARD.encode([0xFF, 0xD8, 0xFF, 0xE0]);
+        assert!(decode_image_data(&format!("data:image/jpg;base64,{}", jpeg)).is_ok());
+        // 无法识别文件头的 image/* 类型不做校验
+        assert!(decode_image_data(&format!("data:image/avif;base64,{}", encoded)).is_ok());
+    }
+}
@@ -1,7 +1,10 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::collections::BTreeMap;
 use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Point {
     pub(crate) x: f32,
     pub(crate) y: f32,
@@ -21,9 +24,368 @@ impl Serialize for Point {
     }
 }
 
-#[derive(Serialize, Debug)]
+// 原始字节在 JSON 中以 base64 字符串输出
+fn serialize_base64<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&STANDARD.encode(bytes))
+}
+
+// 轴对齐矩形，x/y 为左上角
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct Dimension {
+    pub width: f32,
+    pub height: f32,
+}
+
+// 由四个角点推导出的几何信息，坐标单位均为原图像素
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct Geometry {
+    // 外接的轴对齐矩形
+    pub bounding_box: Rect,
+    pub center: Point,
+    // 摆正后图像从左到右的方向相对图像 x 轴的角度（度），范围 (-180, 180]，顺时针为正（图像坐标系 y 轴向下）
+    pub angle: f32,
+    // 条码自身坐标系下的宽高，宽为长边
+    pub size: Dimension,
+    // 单个模块（最窄条/空）的宽高估计，无法估计时为 None
+    pub module_size: Option<Dimension>,
+    // 按图像宽高归一化到 0..1 的角点与外接矩形
+    pub normalized_points: Vec<Point>,
+    pub normalized_bounding_box: Rect,
+}
+
+// 条码的阅读朝向
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Orientation {
+    // 阅读方向相对图像 x 轴顺时针旋转的角度，取 0/90/180/270
+    pub rotation: u16,
+    // 阅读方向的精确角度（度），范围 (-180, 180]
+    pub angle: f32,
+    // 是否为镜像（从背面或经反射看到的）符号，取 rxing 解码 QR 码时给出的镜像标记；
+    // 一维码的镜像与旋转 180 度在条空上无法区分，统一按旋转处理，恒为 false
+    pub mirrored: bool,
+    // 阅读方向是否经过起止符/解码结果确认，为 false 时可能与实际方向相差 180 度
+    pub confirmed: bool,
+}
+
+// Macro PDF417 的分段信息，同一文件的各段 file_id 相同
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct MacroPdf417 {
+    pub segment_index: usize,
+    pub segment_count: Option<usize>,
+    pub file_id: String,
+    pub last_segment: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sender: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub addressee: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_size: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<i32>,
+}
+
+// QR 码 Structured Append 信息：index 从 0 开始，同一消息的各部分 total 与 parity 相同
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct StructuredAppend {
+    pub index: u8,
+    pub total: u8,
+    pub parity: u8,
+}
+
+// 一个 GS1 应用标识符（AI）的取值，已按该 AI 的格式校验
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Gs1Element {
+    pub title: &'static str,
+    pub value: String,
+    // 日期类 AI 换算出的 ISO 8601 日期（时间），日为 00 时取当月最后一天
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    // 计量、金额类 AI 按 AI 末位的小数位数换算出的数值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decimal: Option<f64>,
+    // 带币种的金额类 AI 中的 ISO 4217 数字币种代码
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Gs1Data {
+    // 是否来自 GS1 Digital Link URI
+    pub digital_link: bool,
+    // 以 AI 为键
+    pub elements: BTreeMap<String, Gs1Element>,
+}
+
+// EAN/UPC 商品码的附加信息
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ProductInfo {
+    // 补齐为 14 位的 GTIN
+    pub gtin: String,
+    // UPC-E 展开后的 UPC-A
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upc_a: Option<String>,
+    // 978/979 前缀的图书编号
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isbn13: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isbn10: Option<String>,
+    // 977 前缀的期刊编号
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issn: Option<String>,
+    // GS1 前缀对应的编码组织所在国家或地区，或保留用途
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    // EAN-2/EAN-5 附加码
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub addon: Option<String>,
+    // EAN-2 附加码表示的期刊期号
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issue_number: Option<u8>,
+    // EAN-5 附加码表示的建议零售价
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_price: Option<String>,
+}
+
+// AAMVA 驾照/身份证背面 PDF417 的解析结果，日期均转为 ISO 8601 格式
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct DriverLicense {
+    // AAMVA 标准版本号（01~10）及发证机构自身的版本号
+    pub version: u8,
+    pub jurisdiction_version: Option<u8>,
+    // 发证机构识别号（IIN）
+    pub iin: String,
+    // DL 为驾照，ID 为身份证
+    pub document_type: String,
+    pub license_number: Option<String>,
+    pub family_name: Option<String>,
+    pub given_name: Option<String>,
+    pub middle_name: Option<String>,
+    pub date_of_birth: Option<String>,
+    pub issue_date: Option<String>,
+    pub expiry_date: Option<String>,
+    // M、F 或 X
+    pub sex: Option<String>,
+    pub street: Option<String>,
+    pub city: Option<String>,
+    // 州/省代码
+    pub jurisdiction: Option<String>,
+    pub postal_code: Option<String>,
+    pub country: Option<String>,
+    pub vehicle_class: Option<String>,
+    pub document_discriminator: Option<String>,
+    // 按当前 UTC 日期计算的周岁与是否过期
+    pub age: Option<u32>,
+    pub expired: Option<bool>,
+    // 必填项缺失、日期非法等校验问题，为空表示校验通过
+    pub errors: Vec<String>,
+}
+
+// 二维码常见内容的结构化结果，序列化时以 type 字段区分类型
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Content {
+    Wifi {
+        ssid: String,
+        // WPA、WEP、nopass 等，未指定时为 None
+        security: Option<String>,
+        password: Option<String>,
+        hidden: bool,
+    },
+    // vCard 或 MeCard 联系人
+    Contact {
+        format: &'static str,
+        name: Option<String>,
+        organization: Option<String>,
+        title: Option<String>,
+        phones: Vec<String>,
+        emails: Vec<String>,
+        urls: Vec<String>,
+        addresses: Vec<String>,
+        note: Option<String>,
+    },
+    Geo {
+        latitude: f64,
+        longitude: f64,
+        altitude: Option<f64>,
+        query: Option<String>,
+    },
+    Email {
+        to: Vec<String>,
+        cc: Vec<String>,
+        bcc: Vec<String>,
+        subject: Option<String>,
+        body: Option<String>,
+    },
+    Sms {
+        number: String,
+        body: Option<String>,
+    },
+    Phone {
+        number: String,
+    },
+    // iCalendar VEVENT，时间转为 ISO 8601 格式，带 Z 后缀的为 UTC
+    Event {
+        summary: Option<String>,
+        start: Option<String>,
+        end: Option<String>,
+        location: Option<String>,
+        description: Option<String>,
+    },
+    Url {
+        url: String,
+    },
+    // EMVCo 商户主扫二维码（银联、各国快速支付等）
+    EmvPayment {
+        // 11 为静态码，12 为动态码
+        point_of_initiation: Option<String>,
+        // 商户账户信息模板（ID 02~51），模板内带 GUID（子 ID 00）时取 GUID，否则为原始值
+        merchant_accounts: BTreeMap<String, String>,
+        merchant_category_code: Option<String>,
+        // ISO 4217 数字币种代码
+        currency: Option<String>,
+        amount: Option<String>,
+        country: Option<String>,
+        merchant_name: Option<String>,
+        merchant_city: Option<String>,
+        postal_code: Option<String>,
+        // 末尾 CRC16 是否校验通过
+        crc_valid: bool,
+    },
+    // 欧洲支付委员会 SEPA 转账二维码（EPC069-12，俗称 GiroCode）
+    EpcPayment {
+        version: String,
+        bic: Option<String>,
+        name: String,
+        iban: String,
+        // IBAN 模 97 校验是否通过
+        iban_valid: bool,
+        currency: Option<String>,
+        amount: Option<String>,
+        purpose: Option<String>,
+        reference: Option<String>,
+        text: Option<String>,
+    },
+    DriverLicense(Box<DriverLicense>),
+}
+
+// 由运单号格式推断出的快递承运商
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Waybill {
+    pub number: String,
+    // 承运商代码，如 SF、YTO、ZTO
+    pub carrier: String,
+    pub carrier_name: String,
+    // 0..1，多个承运商号段重叠时会降低
+    pub confidence: f32,
+    // 同样符合号段规则的其他承运商
+    pub alternatives: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Default)]
 pub struct CodeInfo {
+    // 无法无损解码为文本（二进制数据或未知字符集）时为空，此时以 raw 为准
     pub code: String,
+    // 解码得到的原始字节；二维码中字节段与其他编码模式混排或字符集无法识别时无法还原，此时为空、以 code 为准
+    #[serde(serialize_with = "serialize_base64")]
+    pub raw: Vec<u8>,
+    // code 所用的字符集，为 None 表示 raw 不是可识别的文本或 raw 无法还原
+    pub charset: Option<String>,
+    // 符号是否带有 ECI 字符集指示，带有时按其指定的字符集解码而不做猜测
+    pub eci: bool,
+    // 码制。EAN/UPC 由 OpenCV 解码，按条空比对或解码文本确定码制，8 位数字且阅读方向未确认时无法区分 EAN-8 与 UPC-E，为空；
+    // Code 128、Code 39、ITF、PDF417 及二维码取 rxing 给出的码制
     pub category: String,
+    // 解码器给出的 ISO/IEC 15424 符号标识符，如 ]Q1、]d2
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbology_identifier: Option<String>,
     pub points: Vec<Point>,
+    pub geometry: Geometry,
+    pub orientation: Orientation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_correction_level: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub macro_pdf417: Option<MacroPdf417>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structured_append: Option<StructuredAppend>,
+    // 内容为 GS1 元素串或 GS1 Digital Link 且校验通过时的解析结果
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gs1: Option<Gs1Data>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product: Option<ProductInfo>,
+    // 可识别的内容类型（WiFi、联系人、支付等）的解析结果，原始文本仍在 code 中
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<Content>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub waybill: Option<Waybill>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct StructuredPart {
+    pub index: u8,
+    pub code: String,
+}
+
+// 由多个 Structured Append 符号拼接出的完整消息
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct StructuredMessage {
+    pub parity: u8,
+    pub total: u8,
+    // 是否集齐了全部符号
+    pub complete: bool,
+    // 缺失的符号序号
+    pub missing: Vec<u8>,
+    // 全部数据字节的异或是否等于 parity；未集齐或有符号无法还原原始字节时无法校验，为 None
+    pub parity_valid: Option<bool>,
+    pub parts: Vec<StructuredPart>,
+    // 按序号拼接已找到的各部分；多字节字符可能跨符号拆分，因此按拼接后的字节重新解码
+    pub code: String,
+    #[serde(serialize_with = "serialize_base64")]
+    pub raw: Vec<u8>,
+    pub charset: Option<String>,
+}
+
+// 各处理阶段的耗时（毫秒），多个条码的同一阶段累加
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct StageTimings {
+    // 创建 BarcodeDetector 及加载超分辨率模型
+    pub setup: f64,
+    // BarcodeDetector::detect_multi
+    pub detect: f64,
+    // 透视矫正、估计模块宽度及 extract_and_expand
+    pub extract: f64,
+    // enhance_vertical_lines_with_scaling
+    pub enhance: f64,
+    // BarcodeDetector::decode、PDF417 及一维码的 rxing 区域解码、阅读方向及附加码识别
+    pub decode: f64,
+    // rxing 整图识别二维码
+    pub matrix: f64,
+    // GS1、内容类型、运单号解析及 Structured Append 拼接
+    pub post_process: f64,
+    pub total: f64,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct DetectResult {
+    pub codes: Vec<CodeInfo>,
+    pub messages: Vec<StructuredMessage>,
+    // 超出 timeout_ms 或被取消时为 true，此时 codes 只包含已完成的部分
+    pub timed_out: bool,
+    // DecodeOptions::profile 为 true 时给出各阶段耗时
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<StageTimings>,
 }
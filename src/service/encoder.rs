@@ -0,0 +1,532 @@
+use opencv::core::{Mat, Point, Rect, Scalar, Vector, CV_8UC1};
+use opencv::imgcodecs;
+use opencv::imgproc;
+use opencv::prelude::*;
+use serde::Deserialize;
+use crate::basic::Exception;
+use crate::service::upcean::{ean13_modules, gs1_check_digit, parse_digits};
+
+// 可生成的码制，名称与识别结果中的 category 一致，便于生成后回读比对
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Symbology {
+    #[serde(rename = "EAN_13")]
+    Ean13,
+    #[serde(rename = "UPC_A")]
+    UpcA,
+    #[serde(rename = "CODE_128")]
+    Code128,
+    #[serde(rename = "CODE_39")]
+    Code39,
+    #[serde(rename = "ITF")]
+    Itf,
+    #[serde(rename = "QR_CODE")]
+    QrCode,
+}
+
+impl Symbology {
+    pub const ALL: [Symbology; 6] = [Symbology::Ean13, Symbology::UpcA, Symbology::Code128, Symbology::Code39, Symbology::Itf, Symbology::QrCode];
+
+    pub fn category(self) -> &'static str {
+        match self {
+            Symbology::Ean13 => "EAN_13",
+            Symbology::UpcA => "UPC_A",
+            Symbology::Code128 => "CODE_128",
+            Symbology::Code39 => "CODE_39",
+            Symbology::Itf => "ITF",
+            Symbology::QrCode => "QR_CODE",
+        }
+    }
+
+    // 命令行中的码制名，忽略大小写以及 - 和 _，如 ean13、EAN-13、qr
+    pub fn from_name(name: &str) -> Option<Symbology> {
+        let normalized = name.to_ascii_uppercase().replace(['-', '_'], "");
+        match normalized.as_str() {
+            "EAN13" => Some(Symbology::Ean13),
+            "UPCA" => Some(Symbology::UpcA),
+            "CODE128" => Some(Symbology::Code128),
+            "CODE39" => Some(Symbology::Code39),
+            "ITF" => Some(Symbology::Itf),
+            "QR" | "QRCODE" => Some(Symbology::QrCode),
+            _ => None,
+        }
+    }
+
+    fn is_linear(self) -> bool {
+        self != Symbology::QrCode
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum QrErrorCorrection {
+    L,
+    M,
+    Q,
+    H,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RenderOptions {
+    // 每个模块的像素数
+    pub module_size: u32,
+    // 四周静区的模块数，不指定时一维码取 10，QR 码取 4
+    pub quiet_zone: Option<u32>,
+    // 一维码条高的像素数，QR 码忽略
+    pub bar_height: u32,
+    // 一维码是否在条码下方印刷人眼可读的字符
+    pub human_readable: bool,
+    pub qr_error_correction: QrErrorCorrection,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            module_size: 2,
+            quiet_zone: None,
+            bar_height: 80,
+            human_readable: true,
+            qr_error_correction: QrErrorCorrection::M,
+        }
+    }
+}
+
+// 编码结果：width x height 的模块矩阵，按行存放，true 为深色；一维码只有一行
+#[derive(Debug, Clone)]
+pub struct Encoded {
+    pub symbology: Symbology,
+    pub width: usize,
+    pub height: usize,
+    pub modules: Vec<bool>,
+    // 印刷在条码下方的字符，含自动计算的校验位
+    pub text: String,
+}
+
+// Code 128 的 107 个符号（含起始符），每个符号 3 条 3 空的模块宽度，另加终止符 2331112
+const CODE128_PATTERNS: [&str; 107] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212", "221213",
+    "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221", "223211", "221132",
+    "221231", "213212", "223112", "312131", "311222", "321122", "321221", "312212", "322112", "322211",
+    "212123", "212321", "232121", "111323", "131123", "131321", "112313", "132113", "132311", "211313",
+    "231113", "231311", "112133", "112331", "132131", "113123", "113321", "133121", "313121", "211331",
+    "231131", "213113", "213311", "213131", "311123", "311321", "331121", "312113", "312311", "332111",
+    "314111", "221411", "431111", "111224", "111422", "121124", "121421", "141122", "141221", "112214",
+    "112412", "122114", "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111",
+    "111242", "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311", "113141",
+    "114131", "311141", "411131", "211412", "211214", "211232", "2331112",
+];
+const CODE128_FNC1: u16 = 102;
+const CODE128_STOP: u16 = 106;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CodeSet {
+    A,
+    B,
+    C,
+}
+
+// Code 39 的 43 个字符及起止符 *，9 位中为 1 的是宽单元（依次为条、空交替）
+const CODE39_ALPHABET: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-. $/+%";
+const CODE39_PATTERNS: [u16; 43] = [
+    0x034, 0x121, 0x061, 0x160, 0x031, 0x130, 0x070, 0x025, 0x124, 0x064,
+    0x109, 0x049, 0x148, 0x019, 0x118, 0x058, 0x00D, 0x10C, 0x04C, 0x01C,
+    0x103, 0x043, 0x142, 0x013, 0x112, 0x052, 0x007, 0x106, 0x046, 0x016,
+    0x181, 0x0C1, 0x1C0, 0x091, 0x190, 0x0D0, 0x085, 0x184, 0x0C4, 0x0A8,
+    0x0A2, 0x08A, 0x02A,
+];
+const CODE39_ASTERISK: u16 = 0x094;
+
+// ITF 每个数字 5 个单元，为 1 的是宽单元
+const ITF_PATTERNS: [u8; 10] = [0b00110, 0b10001, 0b01001, 0b11000, 0b00101, 0b10100, 0b01100, 0b00011, 0b10010, 0b01010];
+
+// 宽单元与窄单元的宽度比
+const WIDE: usize = 3;
+
+// 按单元宽度依次追加条、空，第一个单元为条
+fn push_widths(modules: &mut Vec<bool>, widths: impl IntoIterator<Item = usize>) {
+    for (i, width) in widths.into_iter().enumerate() {
+        modules.extend(std::iter::repeat_n(i % 2 == 0, width));
+    }
+}
+
+// 12 位时补上校验位，13 位时校验校验位
+fn ean13_digits(data: &str, symbology: &str) -> Result<Vec<u8>, Exception> {
+    let mut digits = parse_digits(data).ok_or_else(|| Exception::new(0, format!("{} data must be digits, got {:?}", symbology, data)))?;
+    match digits.len() {
+        12 => digits.push(gs1_check_digit(&digits)),
+        13 => {
+            let check = gs1_check_digit(&digits[..12]);
+            if check != digits[12] {
+                return Err(Exception::new(0, format!("Invalid {} check digit {}, expected {}", symbology, digits[12], check)));
+            }
+        }
+        n => return Err(Exception::new(0, format!("{} data must be 12 or 13 digits, got {}", symbology, n))),
+    }
+    Ok(digits)
+}
+
+fn encode_ean13(data: &str) -> Result<(Vec<bool>, String), Exception> {
+    let digits = ean13_digits(data, "EAN-13")?;
+    let modules = ean13_modules(&digits).ok_or_else(|| Exception::new(0, "Failed to encode EAN-13"))?;
+    Ok((modules, digits.iter().map(|d| (b'0' + d) as char).collect()))
+}
+
+// UPC-A 即首位为 0 的 EAN-13
+fn encode_upca(data: &str) -> Result<(Vec<bool>, String), Exception> {
+    let digits = ean13_digits(&format!("0{}", data), "UPC-A")?;
+    let modules = ean13_modules(&digits).ok_or_else(|| Exception::new(0, "Failed to encode UPC-A"))?;
+    Ok((modules, digits[1..].iter().map(|d| (b'0' + d) as char).collect()))
+}
+
+// 切换到目标码集：尚未选择码集时输出对应的起始符，否则输出 CODE A/B/C 切换符
+fn switch_set(values: &mut Vec<u16>, set: &mut Option<CodeSet>, target: CodeSet) {
+    if *set == Some(target) {
+        return;
+    }
+    values.push(match (*set, target) {
+        (None, CodeSet::A) => 103,
+        (None, CodeSet::B) => 104,
+        (None, CodeSet::C) => 105,
+        (_, CodeSet::A) => 101,
+        (_, CodeSet::B) => 100,
+        (_, CodeSet::C) => 99,
+    });
+    *set = Some(target);
+}
+
+fn digit_run(data: &[u8], start: usize) -> usize {
+    data[start..].iter().take_while(|b| b.is_ascii_digit()).count()
+}
+
+// 码集切换：连续 4 位以上数字用 C 集两位一组编码（奇数位时先用当前码集编码第一位），
+// 控制字符用 A 集，其余用 B 集；GS（0x1D）编码为 FNC1，用于 GS1-128
+fn code128_values(data: &[u8]) -> Result<Vec<u16>, Exception> {
+    if data.is_empty() {
+        return Err(Exception::new(0, "Code 128 data must not be empty"));
+    }
+    if let Some(b) = data.iter().find(|b| !b.is_ascii()) {
+        return Err(Exception::new(0, format!("Code 128 cannot encode byte 0x{:02X}", b)));
+    }
+    let mut values = Vec::<u16>::new();
+    let mut set: Option<CodeSet> = None;
+    let mut i = 0;
+    while i < data.len() {
+        let run = digit_run(data, i);
+        let whole = i == 0 && run == data.len() && run.is_multiple_of(2);
+        if (run >= 4 && run.is_multiple_of(2)) || whole || (set == Some(CodeSet::C) && run >= 2) {
+            switch_set(&mut values, &mut set, CodeSet::C);
+            for pair in data[i..i + run / 2 * 2].chunks(2) {
+                values.push(((pair[0] - b'0') * 10 + (pair[1] - b'0')) as u16);
+            }
+            i += run / 2 * 2;
+            continue;
+        }
+        let b = data[i];
+        if b == 0x1D {
+            // FNC1 在三个码集中都可用；位于开头时后面通常是 GS1 应用标识符，优先使用 C 集
+            if set.is_none() {
+                switch_set(&mut values, &mut set, if digit_run(data, 1) >= 2 { CodeSet::C } else { CodeSet::B });
+            }
+            values.push(CODE128_FNC1);
+            i += 1;
+            continue;
+        }
+        let target = match (b, set) {
+            (0..=31, _) => CodeSet::A,
+            (96..=127, _) => CodeSet::B,
+            (_, Some(CodeSet::A)) => CodeSet::A,
+            _ => CodeSet::B,
+        };
+        switch_set(&mut values, &mut set, target);
+        values.push(match (target, b) {
+            (CodeSet::A, 0..=31) => b as u16 + 64,
+            _ => b as u16 - 32,
+        });
+        i += 1;
+    }
+
+    let checksum = values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| v as u32 * (i as u32).max(1))
+        .sum::<u32>()
+        % 103;
+    values.push(checksum as u16);
+    values.push(CODE128_STOP);
+    Ok(values)
+}
+
+fn encode_code128(data: &str) -> Result<(Vec<bool>, String), Exception> {
+    let mut modules = Vec::new();
+    for value in code128_values(data.as_bytes())? {
+        push_widths(&mut modules, CODE128_PATTERNS[value as usize].bytes().map(|w| (w - b'0') as usize));
+    }
+    Ok((modules, data.chars().filter(|c| !c.is_control()).collect()))
+}
+
+fn push_code39_char(modules: &mut Vec<bool>, pattern: u16) {
+    push_widths(modules, (0..9).rev().map(|i| if (pattern >> i) & 1 == 1 { WIDE } else { 1 }));
+    // 字符间隔为一个窄空
+    modules.push(false);
+}
+
+fn encode_code39(data: &str) -> Result<(Vec<bool>, String), Exception> {
+    if data.is_empty() {
+        return Err(Exception::new(0, "Code 39 data must not be empty"));
+    }
+    let mut modules = Vec::new();
+    push_code39_char(&mut modules, CODE39_ASTERISK);
+    for c in data.chars() {
+        let index = CODE39_ALPHABET.find(c).ok_or_else(|| Exception::new(0, format!("Code 39 cannot encode {:?}", c)))?;
+        push_code39_char(&mut modules, CODE39_PATTERNS[index]);
+    }
+    push_code39_char(&mut modules, CODE39_ASTERISK);
+    // 终止符之后不需要字符间隔
+    modules.pop();
+    Ok((modules, data.to_string()))
+}
+
+// ITF 两个数字一组交织编码，前一个数字决定 5 个条宽，后一个决定 5 个空宽
+fn encode_itf(data: &str) -> Result<(Vec<bool>, String), Exception> {
+    let digits = parse_digits(data).ok_or_else(|| Exception::new(0, format!("ITF data must be digits, got {:?}", data)))?;
+    if !digits.len().is_multiple_of(2) {
+        return Err(Exception::new(0, format!("ITF data must have an even number of digits, got {}", digits.len())));
+    }
+    let mut modules = Vec::new();
+    push_widths(&mut modules, [1, 1, 1, 1]);
+    for pair in digits.chunks(2) {
+        let (bars, spaces) = (ITF_PATTERNS[pair[0] as usize], ITF_PATTERNS[pair[1] as usize]);
+        let width = |pattern: u8, i: u32| if (pattern >> (4 - i)) & 1 == 1 { WIDE } else { 1 };
+        push_widths(&mut modules, (0..5).flat_map(|i| [width(bars, i), width(spaces, i)]));
+    }
+    push_widths(&mut modules, [WIDE, 1, 1]);
+    Ok((modules, data.to_string()))
+}
+
+fn encode_qr(data: &str, level: QrErrorCorrection) -> Result<Encoded, Exception> {
+    let level = match level {
+        QrErrorCorrection::L => qrcode::EcLevel::L,
+        QrErrorCorrection::M => qrcode::EcLevel::M,
+        QrErrorCorrection::Q => qrcode::EcLevel::Q,
+        QrErrorCorrection::H => qrcode::EcLevel::H,
+    };
+    let code = qrcode::QrCode::with_error_correction_level(data.as_bytes(), level).map_err(|e| Exception::new(0, format!("Failed to encode QR code: {}", e)))?;
+    Ok(Encoded {
+        symbology: Symbology::QrCode,
+        width: code.width(),
+        height: code.width(),
+        modules: code.to_colors().into_iter().map(|c| c == qrcode::Color::Dark).collect(),
+        text: data.to_string(),
+    })
+}
+
+pub fn encode(symbology: Symbology, data: &str, options: &RenderOptions) -> Result<Encoded, Exception> {
+    let (modules, text) = match symbology {
+        Symbology::Ean13 => encode_ean13(data)?,
+        Symbology::UpcA => encode_upca(data)?,
+        Symbology::Code128 => encode_code128(data)?,
+        Symbology::Code39 => encode_code39(data)?,
+        Symbology::Itf => encode_itf(data)?,
+        Symbology::QrCode => return encode_qr(data, options.qr_error_correction),
+    };
+    Ok(Encoded {
+        symbology,
+        width: modules.len(),
+        height: 1,
+        modules,
+        text,
+    })
+}
+
+// 渲染尺寸：条码区域左上角与大小，以及整张图片的大小（像素）
+struct Layout {
+    module: i32,
+    left: i32,
+    top: i32,
+    bar_height: i32,
+    width: i32,
+    height: i32,
+    text: bool,
+    font_scale: f64,
+}
+
+const FONT: i32 = imgproc::FONT_HERSHEY_SIMPLEX;
+
+fn layout(encoded: &Encoded, options: &RenderOptions) -> Result<Layout, Exception> {
+    if options.module_size == 0 {
+        return Err(Exception::new(0, "Module size must be at least 1 pixel"));
+    }
+    let module = options.module_size as i32;
+    let quiet = options.quiet_zone.unwrap_or(if encoded.symbology.is_linear() { 10 } else { 4 }) as i32 * module;
+    let linear = encoded.symbology.is_linear();
+    let bar_height = if linear { options.bar_height.max(1) as i32 } else { encoded.height as i32 * module };
+    let text = linear && options.human_readable && !encoded.text.is_empty();
+    // Hershey 字体在 scale 为 1 时字高约 22 像素，按模块大小缩放
+    let font_scale = module as f64 * 0.4;
+    let text_height = if text { (22.0 * font_scale) as i32 + module * 4 } else { 0 };
+    Ok(Layout {
+        module,
+        left: quiet,
+        top: quiet,
+        bar_height,
+        width: encoded.width as i32 * module + quiet * 2,
+        height: bar_height + text_height + quiet * 2,
+        text,
+        font_scale,
+    })
+}
+
+// 把每一行连续的深色模块合并成矩形 (x, y, 宽, 高)，单位为模块
+fn dark_runs(encoded: &Encoded) -> Vec<(usize, usize, usize)> {
+    let mut runs = Vec::new();
+    for (y, row) in encoded.modules.chunks(encoded.width.max(1)).enumerate() {
+        let mut x = 0;
+        while x < row.len() {
+            if row[x] {
+                let length = row[x..].iter().take_while(|&&m| m).count();
+                runs.push((x, y, length));
+                x += length;
+            } else {
+                x += 1;
+            }
+        }
+    }
+    runs
+}
+
+// 白底黑码的单通道图像
+pub fn render_mat(encoded: &Encoded, options: &RenderOptions) -> Result<Mat, Exception> {
+    let l = layout(encoded, options)?;
+    let cv = |e: opencv::Error| Exception::new(0, format!("Failed to render barcode: {}", e));
+    let mut image = Mat::new_rows_cols_with_default(l.height, l.width, CV_8UC1, Scalar::all(255.0)).map_err(cv)?;
+    let row_height = if encoded.height == 1 { l.bar_height } else { l.module };
+    for (x, y, length) in dark_runs(encoded) {
+        let rect = Rect::new(l.left + x as i32 * l.module, l.top + y as i32 * row_height, length as i32 * l.module, row_height);
+        imgproc::rectangle(&mut image, rect, Scalar::all(0.0), imgproc::FILLED, imgproc::LINE_8, 0).map_err(cv)?;
+    }
+    if l.text {
+        let mut baseline = 0;
+        let size = imgproc::get_text_size(&encoded.text, FONT, l.font_scale, 1, &mut baseline).map_err(cv)?;
+        let origin = Point::new((l.width - size.width) / 2, l.top + l.bar_height + l.module * 2 + size.height);
+        imgproc::put_text(&mut image, &encoded.text, origin, FONT, l.font_scale, Scalar::all(0.0), 1, imgproc::LINE_AA, false).map_err(cv)?;
+    }
+    Ok(image)
+}
+
+pub fn render_png(encoded: &Encoded, options: &RenderOptions) -> Result<Vec<u8>, Exception> {
+    let image = render_mat(encoded, options)?;
+    let mut buffer = Vector::<u8>::new();
+    imgcodecs::imencode(".png", &image, &mut buffer, &Vector::new()).map_err(|e| Exception::new(0, format!("Failed to encode PNG: {}", e)))?;
+    Ok(buffer.to_vec())
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+pub fn render_svg(encoded: &Encoded, options: &RenderOptions) -> Result<String, Exception> {
+    let l = layout(encoded, options)?;
+    let row_height = if encoded.height == 1 { l.bar_height } else { l.module };
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" shape-rendering=\"crispEdges\">\n<rect width=\"{w}\" height=\"{h}\" fill=\"#fff\"/>\n",
+        w = l.width,
+        h = l.height
+    );
+    for (x, y, length) in dark_runs(encoded) {
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>\n",
+            l.left + x as i32 * l.module,
+            l.top + y as i32 * row_height,
+            length as i32 * l.module,
+            row_height
+        ));
+    }
+    if l.text {
+        let font_size = (22.0 * l.font_scale) as i32;
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\" text-anchor=\"middle\">{}</text>\n",
+            l.width / 2,
+            l.top + l.bar_height + l.module * 2 + font_size,
+            font_size,
+            escape_xml(&encoded.text)
+        ));
+    }
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code128_checksum() {
+        // Start B, "Wikipedia", 校验值 88, Stop
+        let values = code128_values(b"Wikipedia").unwrap();
+        assert_eq!(values[0], 104);
+        assert_eq!(values[values.len() - 2], 88);
+        assert_eq!(values[values.len() - 1], CODE128_STOP);
+    }
+
+    #[test]
+    fn code128_uses_code_set_c_for_digits() {
+        assert_eq!(code128_values(b"123456").unwrap()[..4], [105, 12, 34, 56]);
+        // 奇数位数字先用 B 集编码第一位
+        assert_eq!(code128_values(b"12345").unwrap()[..5], [104, 17, 99, 23, 45]);
+        // GS1-128：FNC1 + (01)
+        assert_eq!(code128_values(b"\x1D0112345678901231").unwrap()[..3], [105, CODE128_FNC1, 1]);
+        assert!(code128_values("é".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn linear_module_counts() {
+        let options = RenderOptions::default();
+        let ean = encode(Symbology::Ean13, "690123456789", &options).unwrap();
+        assert_eq!((ean.width, ean.height, ean.text.as_str()), (95, 1, "6901234567892"));
+        assert!(encode(Symbology::Ean13, "6901234567891", &options).is_err());
+        assert_eq!(encode(Symbology::UpcA, "03600029145", &options).unwrap().text, "036000291452");
+        // 每个字符 3 宽 6 窄共 15 个模块，加 1 个字符间隔
+        assert_eq!(encode(Symbology::Code39, "AB", &options).unwrap().width, 16 * 4 - 1);
+        // 起始符 4 + 每对数字 2 * (2 * 3 + 3) + 终止符 5
+        assert_eq!(encode(Symbology::Itf, "1234", &options).unwrap().width, 4 + 2 * 18 + 5);
+        assert!(encode(Symbology::Itf, "123", &options).is_err());
+        assert!(encode(Symbology::Code39, "ab", &options).is_err());
+    }
+
+    #[test]
+    fn qr_code_is_square() {
+        let qr = encode(Symbology::QrCode, "https://example.com", &RenderOptions::default()).unwrap();
+        assert_eq!(qr.width, qr.height);
+        assert_eq!(qr.modules.len(), qr.width * qr.height);
+        assert!((qr.width - 17).is_multiple_of(4));
+    }
+
+    #[test]
+    fn layout_applies_module_size_and_quiet_zone() {
+        let options = RenderOptions {
+            module_size: 3,
+            quiet_zone: Some(5),
+            bar_height: 40,
+            human_readable: false,
+            ..RenderOptions::default()
+        };
+        let encoded = encode(Symbology::Itf, "1234", &options).unwrap();
+        let l = layout(&encoded, &options).unwrap();
+        assert_eq!((l.left, l.top, l.width, l.height), (15, 15, 45 * 3 + 30, 40 + 30));
+        // QR 码默认静区 4 个模块，高度按模块数计算
+        let qr = encode(Symbology::QrCode, "A", &RenderOptions::default()).unwrap();
+        let l = layout(&qr, &RenderOptions::default()).unwrap();
+        assert_eq!((l.width, l.height, l.text), ((21 + 8) * 2, (21 + 8) * 2, false));
+        assert!(layout(&encoded, &RenderOptions { module_size: 0, ..RenderOptions::default() }).is_err());
+    }
+
+    #[test]
+    fn svg_contains_bars_and_text() {
+        let options = RenderOptions::default();
+        let encoded = encode(Symbology::Code128, "A<B", &options).unwrap();
+        let svg = render_svg(&encoded, &options).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(">A&lt;B</text>"));
+        assert_eq!(svg.matches("<rect").count(), 1 + dark_runs(&encoded).len());
+    }
+}
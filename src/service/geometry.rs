@@ -0,0 +1,240 @@
+use crate::service::dto::{Dimension, Geometry, Orientation, Point, Rect};
+
+fn distance(a: &Point, b: &Point) -> f32 {
+    ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt()
+}
+
+// 将角度归一化到 (-180, 180]
+pub(crate) fn normalize_angle(angle: f32) -> f32 {
+    let mut a = angle % 360.0;
+    if a <= -180.0 {
+        a += 360.0;
+    } else if a > 180.0 {
+        a -= 360.0;
+    }
+    a
+}
+
+fn bounding_box(points: &[Point]) -> Rect {
+    if points.is_empty() {
+        return Rect::default();
+    }
+    let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+    let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+    for p in points {
+        min_x = min_x.min(p.x);
+        min_y = min_y.min(p.y);
+        max_x = max_x.max(p.x);
+        max_y = max_y.max(p.y);
+    }
+    Rect {
+        x: min_x,
+        y: min_y,
+        width: max_x - min_x,
+        height: max_y - min_y,
+    }
+}
+
+fn normalize_point(p: &Point, image_width: f32, image_height: f32) -> Point {
+    Point {
+        x: (p.x / image_width).clamp(0.0, 1.0),
+        y: (p.y / image_height).clamp(0.0, 1.0),
+    }
+}
+
+// 摆正后图像从左到右的方向在原图中对应的向量。角点顺序与 extract_and_rotate_if_needed 一致：
+// points[0]->points[1] 为“宽”，points[0]->points[3] 为“高”；高大于宽时摆正会顺时针旋转 90 度，
+// 此时从左到右对应原图中 points[3]->points[0] 的方向
+pub(crate) fn reading_axis(points: &[Point]) -> (f32, f32) {
+    let width = distance(&points[0], &points[1]).max(distance(&points[3], &points[2]));
+    let height = distance(&points[0], &points[3]).max(distance(&points[1], &points[2]));
+    if width >= height {
+        (
+            (points[1].x - points[0].x + points[2].x - points[3].x) / 2.0,
+            (points[1].y - points[0].y + points[2].y - points[3].y) / 2.0,
+        )
+    } else {
+        (
+            (points[0].x - points[3].x + points[1].x - points[2].x) / 2.0,
+            (points[0].y - points[3].y + points[1].y - points[2].y) / 2.0,
+        )
+    }
+}
+
+pub(crate) fn compute_geometry(points: &[Point], image_width: i32, image_height: i32, module_size: Option<Dimension>) -> Geometry {
+    let bbox = bounding_box(points);
+    let count = points.len().max(1) as f32;
+    let center = Point {
+        x: points.iter().map(|p| p.x).sum::<f32>() / count,
+        y: points.iter().map(|p| p.y).sum::<f32>() / count,
+    };
+
+    // 非四边形（理论上不会出现）时只提供外接矩形相关信息
+    let (size, angle) = if points.len() == 4 {
+        let width = (distance(&points[0], &points[1]) + distance(&points[3], &points[2])) / 2.0;
+        let height = (distance(&points[0], &points[3]) + distance(&points[1], &points[2])) / 2.0;
+        let (dx, dy) = reading_axis(points);
+        let size = Dimension {
+            width: width.max(height),
+            height: width.min(height),
+        };
+        (size, normalize_angle(dy.atan2(dx).to_degrees()))
+    } else {
+        (Dimension { width: bbox.width, height: bbox.height }, 0.0)
+    };
+
+    let (w, h) = (image_width.max(1) as f32, image_height.max(1) as f32);
+    let normalized_points = points.iter().map(|p| normalize_point(p, w, h)).collect::<Vec<Point>>();
+    let top_left = normalize_point(&Point { x: bbox.x, y: bbox.y }, w, h);
+    let bottom_right = normalize_point(&Point { x: bbox.x + bbox.width, y: bbox.y + bbox.height }, w, h);
+
+    Geometry {
+        bounding_box: bbox,
+        center,
+        angle,
+        size,
+        module_size,
+        normalized_points,
+        normalized_bounding_box: Rect {
+            x: top_left.x,
+            y: top_left.y,
+            width: bottom_right.x - top_left.x,
+            height: bottom_right.y - top_left.y,
+        },
+    }
+}
+
+// 由阅读方向角度得到朝向：reversed 表示实际阅读方向与摆正后的从左到右相反
+pub(crate) fn orientation_from_angle(angle: f32, reversed: bool, mirrored: bool, confirmed: bool) -> Orientation {
+    let angle = normalize_angle(if reversed { angle + 180.0 } else { angle });
+    let rotation = ((angle.rem_euclid(360.0) / 90.0).round() as u16 % 4) * 90;
+    Orientation {
+        rotation,
+        angle,
+        mirrored,
+        confirmed,
+    }
+}
+
+// 取行内最亮与最暗的中值作为二值化阈值，对比度过低时视为没有条空
+fn row_threshold(row: &[u8]) -> Option<u8> {
+    let min = *row.iter().min()? as u16;
+    let max = *row.iter().max()? as u16;
+    if max - min < 32 {
+        return None;
+    }
+    Some(((min + max) / 2) as u8)
+}
+
+// 将一行灰度像素按明暗切分为连续的游程（长度，是否为暗条）
+pub(crate) fn run_lengths(row: &[u8]) -> Vec<(usize, bool)> {
+    let mut runs = Vec::new();
+    let mid = match row_threshold(row) {
+        Some(mid) => mid,
+        None => return runs,
+    };
+    let mut current = row[0] < mid;
+    let mut length = 0usize;
+    for &pixel in row {
+        let dark = pixel < mid;
+        if dark == current {
+            length += 1;
+        } else {
+            runs.push((length, current));
+            current = dark;
+            length = 1;
+        }
+    }
+    runs.push((length, current));
+    runs
+}
+
+// 从横穿条码的一行像素估计模块宽度（最窄条/空的像素宽度）
+pub(crate) fn module_width_from_row(row: &[u8]) -> Option<f32> {
+    let runs = run_lengths(row);
+    // 去掉首尾游程：通常是静区或被裁切的残缺条
+    if runs.len() < 8 {
+        return None;
+    }
+    let mut lengths = runs[1..runs.len() - 1].iter().map(|r| r.0 as f32).collect::<Vec<f32>>();
+    lengths.sort_by(f32::total_cmp);
+
+    // 取第 25 百分位作为窄单元的初值，再对接近它的游程取平均，降低单像素噪声的影响
+    let seed = lengths[lengths.len() / 4];
+    let narrow = lengths.iter().filter(|&&l| l < seed * 1.5).collect::<Vec<&f32>>();
+    if narrow.is_empty() {
+        return Some(seed);
+    }
+    Some(narrow.iter().copied().sum::<f32>() / narrow.len() as f32)
+}
+
+// 将 [start, end) 区间等分为 n 个模块，取每个模块中心像素的明暗
+pub(crate) fn sample_modules(row: &[u8], start: usize, end: usize, n: usize) -> Option<Vec<bool>> {
+    let mid = row_threshold(row)?;
+    let span = end.min(row.len()).checked_sub(start)? as f32;
+    if n == 0 || span < n as f32 {
+        return None;
+    }
+    Some(
+        (0..n)
+            .map(|i| {
+                let x = start + ((i as f32 + 0.5) * span / n as f32) as usize;
+                row[x.min(end - 1)] < mid
+            })
+            .collect(),
+    )
+}
+
+// 将扫描线上首个暗像素到最后一个暗像素之间的区域重采样为 expected.len() 个模块，
+// 分别返回与期望序列正向、反向比对的吻合比例
+pub(crate) fn match_modules(row: &[u8], expected: &[bool]) -> Option<(f32, f32)> {
+    let mid = row_threshold(row)?;
+    let start = row.iter().position(|&p| p < mid)?;
+    let end = row.iter().rposition(|&p| p < mid)? + 1;
+    let n = expected.len();
+    let span = (end - start) as f32;
+    if n == 0 || span < n as f32 {
+        return None;
+    }
+    let mut forward = 0usize;
+    let mut reversed = 0usize;
+    for i in 0..n {
+        let x = start + ((i as f32 + 0.5) * span / n as f32) as usize;
+        let dark = row[x.min(end - 1)] < mid;
+        if dark == expected[i] {
+            forward += 1;
+        }
+        if dark == expected[n - 1 - i] {
+            reversed += 1;
+        }
+    }
+    Some((forward as f32 / n as f32, reversed as f32 / n as f32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orientation_snaps_to_quarter_turns() {
+        let o = orientation_from_angle(-93.0, false, false, true);
+        assert_eq!((o.rotation, o.angle), (270, -93.0));
+        // 反向阅读时角度加 180 度
+        let o = orientation_from_angle(10.0, true, false, true);
+        assert_eq!((o.rotation, o.angle), (180, -170.0));
+        assert!(orientation_from_angle(0.0, false, true, true).mirrored);
+    }
+
+    #[test]
+    fn module_width_ignores_edge_runs() {
+        // 静区 + 条空宽度 2/4 像素交替 + 静区
+        let mut row = vec![255u8; 20];
+        for i in 0..12 {
+            let width = if i % 3 == 0 { 4 } else { 2 };
+            row.extend(std::iter::repeat_n(if i % 2 == 0 { 0 } else { 255 }, width));
+        }
+        row.extend([255u8; 20]);
+        assert_eq!(module_width_from_row(&row), Some(2.0));
+        assert_eq!(module_width_from_row(&[128u8; 64]), None);
+    }
+}
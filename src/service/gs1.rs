@@ -0,0 +1,534 @@
+use crate::basic::Exception;
+use crate::service::calendar::{days_in_month, today};
+use crate::service::dto::{CodeInfo, Gs1Data, Gs1Element};
+use crate::service::upcean::{gs1_check_digit, parse_digits};
+
+// FNC1 在解码文本中以 GS（0x1D）表示，用于结束变长 AI
+const GS: char = '\u{1D}';
+
+// Digital Link 路径必须以主键 AI 开头，避免把普通网址中恰好像 AI 的路径段当作 GS1 数据
+const PRIMARY_KEYS: [&str; 15] = ["00", "01", "253", "255", "401", "402", "414", "417", "8003", "8004", "8006", "8010", "8013", "8017", "8018"];
+
+// 表明内容为 GS1 元素串的符号标识符：GS1-128、GS1 DataMatrix、GS1 QR、GS1 DataBar、GS1 Aztec
+const GS1_IDENTIFIERS: [&str; 7] = ["]C1", "]d2", "]Q3", "]e0", "]z1", "]d5", "]Q4"];
+
+#[derive(Clone, Copy)]
+enum Format {
+    // 定长数字
+    Numeric(usize),
+    // 最长 n 位数字
+    NumericUpTo(usize),
+    // 最长 n 位 GS1 字符集 82 中的字符
+    Alphanumeric(usize),
+    // 定长数字后跟最长 n 位字符，如 253 的 N13+X..17
+    NumericThenAlphanumeric(usize, usize),
+    // YYMMDD
+    Date,
+    // YYMMDDhhmm
+    DateTime,
+    // N6，AI 第 4 位为小数位数
+    Measure,
+    // N..15，AI 第 4 位为小数位数
+    Amount,
+    // N3 币种 + N..15，AI 第 4 位为小数位数
+    CurrencyAmount,
+}
+
+struct AiSpec {
+    // 计量、金额类只列出前 3 位，第 4 位为小数位数
+    ai: &'static str,
+    title: &'static str,
+    format: Format,
+    // 末位（或定长数字部分的末位）为 GS1 校验位
+    check_digit: bool,
+}
+
+const fn spec(ai: &'static str, title: &'static str, format: Format, check_digit: bool) -> AiSpec {
+    AiSpec { ai, title, format, check_digit }
+}
+
+const AI_SPECS: [AiSpec; 78] = [
+    spec("00", "SSCC", Format::Numeric(18), true),
+    spec("01", "GTIN", Format::Numeric(14), true),
+    spec("02", "CONTENT", Format::Numeric(14), true),
+    spec("10", "BATCH/LOT", Format::Alphanumeric(20), false),
+    spec("11", "PROD DATE", Format::Date, false),
+    spec("12", "DUE DATE", Format::Date, false),
+    spec("13", "PACK DATE", Format::Date, false),
+    spec("15", "BEST BEFORE", Format::Date, false),
+    spec("16", "SELL BY", Format::Date, false),
+    spec("17", "USE BY OR EXPIRY", Format::Date, false),
+    spec("20", "VARIANT", Format::Numeric(2), false),
+    spec("21", "SERIAL", Format::Alphanumeric(20), false),
+    spec("22", "CPV", Format::Alphanumeric(20), false),
+    spec("235", "TPX", Format::Alphanumeric(28), false),
+    spec("240", "ADDITIONAL ID", Format::Alphanumeric(30), false),
+    spec("241", "CUST. PART No.", Format::Alphanumeric(30), false),
+    spec("250", "SECONDARY SERIAL", Format::Alphanumeric(30), false),
+    spec("251", "REF. TO SOURCE", Format::Alphanumeric(30), false),
+    spec("253", "GDTI", Format::NumericThenAlphanumeric(13, 17), true),
+    spec("254", "GLN EXTENSION COMPONENT", Format::Alphanumeric(20), false),
+    spec("255", "GCN", Format::NumericThenAlphanumeric(13, 12), true),
+    spec("30", "VAR. COUNT", Format::NumericUpTo(8), false),
+    spec("310", "NET WEIGHT (kg)", Format::Measure, false),
+    spec("311", "LENGTH (m)", Format::Measure, false),
+    spec("312", "WIDTH (m)", Format::Measure, false),
+    spec("313", "HEIGHT (m)", Format::Measure, false),
+    spec("314", "AREA (m2)", Format::Measure, false),
+    spec("315", "NET VOLUME (l)", Format::Measure, false),
+    spec("316", "NET VOLUME (m3)", Format::Measure, false),
+    spec("320", "NET WEIGHT (lb)", Format::Measure, false),
+    spec("330", "GROSS WEIGHT (kg)", Format::Measure, false),
+    spec("331", "LENGTH (m), log", Format::Measure, false),
+    spec("332", "WIDTH (m), log", Format::Measure, false),
+    spec("333", "HEIGHT (m), log", Format::Measure, false),
+    spec("334", "AREA (m2), log", Format::Measure, false),
+    spec("335", "VOLUME (l), log", Format::Measure, false),
+    spec("336", "VOLUME (m3), log", Format::Measure, false),
+    spec("37", "COUNT", Format::NumericUpTo(8), false),
+    spec("390", "AMOUNT", Format::Amount, false),
+    spec("391", "AMOUNT", Format::CurrencyAmount, false),
+    spec("392", "PRICE", Format::Amount, false),
+    spec("393", "PRICE", Format::CurrencyAmount, false),
+    spec("400", "ORDER NUMBER", Format::Alphanumeric(30), false),
+    spec("401", "GINC", Format::Alphanumeric(30), false),
+    spec("402", "GSIN", Format::Numeric(17), true),
+    spec("403", "ROUTE", Format::Alphanumeric(30), false),
+    spec("410", "SHIP TO LOC", Format::Numeric(13), true),
+    spec("411", "BILL TO", Format::Numeric(13), true),
+    spec("412", "PURCHASE FROM", Format::Numeric(13), true),
+    spec("413", "SHIP FOR LOC", Format::Numeric(13), true),
+    spec("414", "LOC No.", Format::Numeric(13), true),
+    spec("415", "PAY TO", Format::Numeric(13), true),
+    spec("416", "PROD/SERV LOC", Format::Numeric(13), true),
+    spec("417", "PARTY", Format::Numeric(13), true),
+    spec("420", "SHIP TO POST", Format::Alphanumeric(20), false),
+    spec("421", "SHIP TO POST", Format::NumericThenAlphanumeric(3, 9), false),
+    spec("422", "ORIGIN", Format::Numeric(3), false),
+    spec("7003", "EXPIRY TIME", Format::DateTime, false),
+    spec("8003", "GRAI", Format::NumericThenAlphanumeric(14, 16), true),
+    spec("8004", "GIAI", Format::Alphanumeric(30), false),
+    spec("8005", "PRICE PER UNIT", Format::Numeric(6), false),
+    // 14 位 GTIN 后接 2 位件号与 2 位总件数，校验位不在末位，这里不校验
+    spec("8006", "ITIP", Format::Numeric(18), false),
+    spec("8010", "CPID", Format::Alphanumeric(30), false),
+    spec("8013", "GMN", Format::Alphanumeric(25), false),
+    spec("8017", "GSRN - PROVIDER", Format::Numeric(18), true),
+    spec("8018", "GSRN - RECIPIENT", Format::Numeric(18), true),
+    spec("8020", "REF. No.", Format::Alphanumeric(25), false),
+    spec("8200", "PRODUCT URL", Format::Alphanumeric(70), false),
+    spec("90", "INTERNAL", Format::Alphanumeric(30), false),
+    spec("91", "INTERNAL", Format::Alphanumeric(90), false),
+    spec("92", "INTERNAL", Format::Alphanumeric(90), false),
+    spec("93", "INTERNAL", Format::Alphanumeric(90), false),
+    spec("94", "INTERNAL", Format::Alphanumeric(90), false),
+    spec("95", "INTERNAL", Format::Alphanumeric(90), false),
+    spec("96", "INTERNAL", Format::Alphanumeric(90), false),
+    spec("97", "INTERNAL", Format::Alphanumeric(90), false),
+    spec("98", "INTERNAL", Format::Alphanumeric(90), false),
+    spec("99", "INTERNAL", Format::Alphanumeric(90), false),
+];
+
+impl Format {
+    // 定长格式的数据长度，变长格式以 FNC1 或结尾结束
+    fn fixed_length(self) -> Option<usize> {
+        match self {
+            Format::Numeric(n) => Some(n),
+            Format::Date | Format::Measure => Some(6),
+            Format::DateTime => Some(10),
+            _ => None,
+        }
+    }
+}
+
+// 按最长匹配查找 AI，返回 AI 本身及其定义
+fn lookup(data: &str) -> Option<(&str, &'static AiSpec)> {
+    for length in 2..=4 {
+        let ai = data.get(..length)?;
+        if !ai.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let found = AI_SPECS.iter().find(|s| match s.format {
+            Format::Measure | Format::Amount | Format::CurrencyAmount => length == 4 && ai.starts_with(s.ai),
+            _ => s.ai == ai,
+        });
+        if let Some(spec) = found {
+            return Some((ai, spec));
+        }
+    }
+    None
+}
+
+// GS1 字符集 82：可打印 ASCII 中除空格、#、$、@、[、\、]、^、`、{、|、}、~ 之外的字符
+fn is_cset82(c: char) -> bool {
+    c.is_ascii_graphic() && !matches!(c, '#' | '$' | '@' | '[' | '\\' | ']' | '^' | '`' | '{' | '|' | '}' | '~')
+}
+
+fn check_numeric(ai: &str, value: &str, min: usize, max: usize) -> Result<(), Exception> {
+    if value.len() < min || value.len() > max || !value.bytes().all(|b| b.is_ascii_digit()) {
+        let length = if min == max { format!("{}", min) } else { format!("{}..{}", min, max) };
+        return Err(Exception::new(0, format!("AI ({}) requires {} digits, got {:?}", ai, length, value)));
+    }
+    Ok(())
+}
+
+fn check_alphanumeric(ai: &str, value: &str, max: usize) -> Result<(), Exception> {
+    if value.is_empty() || value.chars().count() > max {
+        return Err(Exception::new(0, format!("AI ({}) requires 1..{} characters, got {}", ai, max, value.chars().count())));
+    }
+    if let Some(c) = value.chars().find(|&c| !is_cset82(c)) {
+        return Err(Exception::new(0, format!("AI ({}) contains invalid character {:?}", ai, c)));
+    }
+    Ok(())
+}
+
+fn check_digit(ai: &str, digits: &str) -> Result<(), Exception> {
+    let digits = parse_digits(digits).ok_or_else(|| Exception::new(0, format!("AI ({}) is not numeric", ai)))?;
+    let (data, check) = digits.split_at(digits.len() - 1);
+    if gs1_check_digit(data) != check[0] {
+        return Err(Exception::new(0, format!("AI ({}) has invalid check digit", ai)));
+    }
+    Ok(())
+}
+
+// GS1 通用规范 7.12：两位年份落在当前年份前 49 年到后 50 年的窗口内
+fn full_year(yy: i32) -> i32 {
+    let current = today().0;
+    let century = current - current.rem_euclid(100);
+    let diff = yy - current.rem_euclid(100);
+    if diff >= 51 {
+        century - 100 + yy
+    } else if diff <= -50 {
+        century + 100 + yy
+    } else {
+        century + yy
+    }
+}
+
+// YYMMDD 转为 ISO 8601 日期，日为 00 表示当月最后一天
+fn parse_date(ai: &str, value: &str) -> Result<String, Exception> {
+    let field = |range: std::ops::Range<usize>| value[range].parse::<u32>().unwrap_or(0);
+    let year = full_year(field(0..2) as i32);
+    let month = field(2..4);
+    let mut day = field(4..6);
+    if !(1..=12).contains(&month) {
+        return Err(Exception::new(0, format!("AI ({}) has invalid month {:02}", ai, month)));
+    }
+    if day == 0 {
+        day = days_in_month(year, month);
+    } else if day > days_in_month(year, month) {
+        return Err(Exception::new(0, format!("AI ({}) has invalid day {:02}", ai, day)));
+    }
+    Ok(format!("{:04}-{:02}-{:02}", year, month, day))
+}
+
+fn decimal(ai: &str, digits: &str) -> Option<f64> {
+    let places = ai[3..4].parse::<i32>().ok()?;
+    Some(digits.parse::<f64>().ok()? / 10f64.powi(places))
+}
+
+// 按 AI 定义校验取值并生成结构化元素
+fn parse_element(ai: &str, spec: &AiSpec, value: &str) -> Result<Gs1Element, Exception> {
+    let mut element = Gs1Element {
+        title: spec.title,
+        value: value.to_string(),
+        date: None,
+        decimal: None,
+        currency: None,
+    };
+    match spec.format {
+        Format::Numeric(n) => check_numeric(ai, value, n, n)?,
+        Format::NumericUpTo(n) => check_numeric(ai, value, 1, n)?,
+        Format::Alphanumeric(n) => check_alphanumeric(ai, value, n)?,
+        Format::NumericThenAlphanumeric(n, m) => {
+            let numeric = value.get(..n).ok_or_else(|| Exception::new(0, format!("AI ({}) is too short", ai)))?;
+            check_numeric(ai, numeric, n, n)?;
+            if value.len() > n {
+                check_alphanumeric(ai, &value[n..], m)?;
+            }
+            if spec.check_digit {
+                check_digit(ai, numeric)?;
+            }
+        }
+        Format::Date => {
+            check_numeric(ai, value, 6, 6)?;
+            element.date = Some(parse_date(ai, value)?);
+        }
+        Format::DateTime => {
+            check_numeric(ai, value, 10, 10)?;
+            let (hour, minute) = (value[6..8].parse::<u32>().unwrap_or(0), value[8..10].parse::<u32>().unwrap_or(0));
+            if hour > 23 || minute > 59 {
+                return Err(Exception::new(0, format!("AI ({}) has invalid time {}", ai, &value[6..10])));
+            }
+            element.date = Some(format!("{}T{:02}:{:02}", parse_date(ai, &value[..6])?, hour, minute));
+        }
+        Format::Measure => {
+            check_numeric(ai, value, 6, 6)?;
+            element.decimal = decimal(ai, value);
+        }
+        Format::Amount => {
+            check_numeric(ai, value, 1, 15)?;
+            element.decimal = decimal(ai, value);
+        }
+        Format::CurrencyAmount => {
+            check_numeric(ai, value, 4, 18)?;
+            element.currency = Some(value[..3].to_string());
+            element.decimal = decimal(ai, &value[3..]);
+        }
+    }
+    if spec.check_digit && matches!(spec.format, Format::Numeric(_)) {
+        check_digit(ai, value)?;
+    }
+    Ok(element)
+}
+
+fn insert(data: &mut Gs1Data, ai: &str, element: Gs1Element) -> Result<(), Exception> {
+    if data.elements.contains_key(ai) {
+        return Err(Exception::new(0, format!("AI ({}) appears more than once", ai)));
+    }
+    data.elements.insert(ai.to_string(), element);
+    Ok(())
+}
+
+// 括号形式的人工可读文本，如 (01)09506000134352(17)201231(10)ABC123
+fn parse_bracketed(text: &str) -> Result<Gs1Data, Exception> {
+    let mut data = Gs1Data::default();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let body = rest.strip_prefix('(').ok_or_else(|| Exception::new(0, format!("Expected '(' before {:?}", rest)))?;
+        let close = body.find(')').ok_or_else(|| Exception::new(0, "Unclosed application identifier"))?;
+        let ai = &body[..close];
+        let (matched, spec) = lookup(ai).filter(|(m, _)| m.len() == ai.len()).ok_or_else(|| Exception::new(0, format!("Unknown application identifier ({})", ai)))?;
+        let value_end = body[close + 1..].find('(').map(|i| close + 1 + i).unwrap_or(body.len());
+        insert(&mut data, matched, parse_element(matched, spec, &body[close + 1..value_end])?)?;
+        rest = &body[value_end..];
+    }
+    Ok(data)
+}
+
+// 以 FNC1（GS）分隔的元素串，定长 AI 之后不需要分隔符
+fn parse_element_string(text: &str) -> Result<Gs1Data, Exception> {
+    let mut data = Gs1Data::default();
+    let mut pos = 0usize;
+    while pos < text.len() {
+        if text[pos..].starts_with(GS) {
+            pos += GS.len_utf8();
+            continue;
+        }
+        let (ai, spec) = lookup(&text[pos..]).ok_or_else(|| Exception::new(0, format!("Unknown application identifier at position {}", pos)))?;
+        let ai = ai.to_string();
+        pos += ai.len();
+        let end = match spec.format.fixed_length() {
+            Some(n) => pos + n,
+            None => text[pos..].find(GS).map(|i| pos + i).unwrap_or(text.len()),
+        };
+        let value = text.get(pos..end).ok_or_else(|| Exception::new(0, format!("AI ({}) is truncated", ai)))?;
+        insert(&mut data, &ai, parse_element(&ai, spec, value)?)?;
+        pos = end;
+    }
+    if data.elements.is_empty() {
+        return Err(Exception::new(0, "Empty GS1 element string"));
+    }
+    Ok(data)
+}
+
+// 解析 GS1 元素串：可带符号标识符前缀（]C1、]d2、]Q3 等）或以 FNC1 开头，也接受括号形式
+pub fn parse_gs1(text: &str) -> Result<Gs1Data, Exception> {
+    let mut text = text;
+    if let Some(prefix) = GS1_IDENTIFIERS.iter().find(|p| text.starts_with(*p)) {
+        text = &text[prefix.len()..];
+    }
+    let text = text.trim_start_matches(GS);
+    if text.starts_with('(') {
+        return parse_bracketed(text);
+    }
+    parse_element_string(text)
+}
+
+pub(crate) fn percent_decode(value: &str) -> Result<String, Exception> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            // from_str_radix 接受前导 '+'，需先确认两位都是十六进制数字
+            let hex = value
+                .get(i + 1..i + 3)
+                .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|h| u8::from_str_radix(h, 16).ok());
+            decoded.push(hex.ok_or_else(|| Exception::new(0, format!("Invalid percent encoding in {:?}", value)))?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| Exception::new(0, format!("Invalid UTF-8 in {:?}", value)))
+}
+
+// 解析 GS1 Digital Link URI，如 https://id.gs1.org/01/09506000134352/10/ABC123?17=201231。
+// 路径中 AI 与取值成对出现，从第一个主键 AI（GTIN、SSCC、GLN 等）开始，之前的路径段视为自定义前缀；
+// 查询参数中的数字键同样视为 AI
+pub fn parse_digital_link(uri: &str) -> Result<Gs1Data, Exception> {
+    let rest = uri
+        .strip_prefix("https://")
+        .or_else(|| uri.strip_prefix("http://"))
+        .ok_or_else(|| Exception::new(0, "GS1 Digital Link must be an http(s) URI"))?;
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (path, query) = match rest.split_once('?') {
+        Some((path, query)) => (path, query),
+        None => (rest, ""),
+    };
+
+    let mut data = Gs1Data {
+        digital_link: true,
+        ..Default::default()
+    };
+    // 跳过主机名及可能的自定义路径前缀
+    let segments = path.split('/').skip(1).collect::<Vec<&str>>();
+    let start = segments
+        .iter()
+        .position(|s| PRIMARY_KEYS.contains(s))
+        .ok_or_else(|| Exception::new(0, "No GS1 primary key in URI path"))?;
+    let pairs = &segments[start..];
+    if pairs.len() % 2 != 0 {
+        return Err(Exception::new(0, "GS1 Digital Link path must contain AI/value pairs"));
+    }
+    for pair in pairs.chunks(2) {
+        let (ai, spec) = lookup(pair[0]).filter(|(ai, _)| ai.len() == pair[0].len()).ok_or_else(|| Exception::new(0, format!("Unknown application identifier ({})", pair[0])))?;
+        let mut value = percent_decode(pair[1])?;
+        // Digital Link 允许省略 GTIN 的前导 0
+        if ai == "01" && value.len() < 14 {
+            value = format!("{:0>14}", value);
+        }
+        insert(&mut data, ai, parse_element(ai, spec, &value)?)?;
+    }
+    for param in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = param.split_once('=').unwrap_or((param, ""));
+        // 非 AI 的查询参数（如 linkType）忽略
+        if let Some((ai, spec)) = lookup(key).filter(|(ai, _)| ai.len() == key.len()) {
+            insert(&mut data, ai, parse_element(ai, spec, &percent_decode(value)?)?)?;
+        }
+    }
+    Ok(data)
+}
+
+// 判断 CodeInfo 的内容是否为 GS1 数据并解析：符号标识符表明 GS1、以 FNC1 开头、
+// 括号形式或 http(s) 开头的 Digital Link；内容不是 GS1 数据时返回 None
+pub fn parse_code(code: &CodeInfo) -> Option<Result<Gs1Data, Exception>> {
+    let text = code.code.as_str();
+    let gs1_symbol = code.symbology_identifier.as_deref().is_some_and(|id| GS1_IDENTIFIERS.contains(&id));
+    if gs1_symbol || text.starts_with(GS) || GS1_IDENTIFIERS.iter().any(|p| text.starts_with(p)) {
+        return Some(parse_gs1(text));
+    }
+    if text.starts_with('(') && lookup(&text[1..]).is_some() {
+        return Some(parse_gs1(text));
+    }
+    if text.starts_with("http://") || text.starts_with("https://") {
+        // 普通网址不是 Digital Link，不作为错误返回
+        return parse_digital_link(text).ok().map(Ok);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bracketed_text() {
+        let data = parse_gs1("(01)09506000134352(17)201231(10)ABC123").unwrap();
+        assert!(!data.digital_link);
+        assert_eq!(data.elements.len(), 3);
+        assert_eq!(data.elements["01"].title, "GTIN");
+        assert_eq!(data.elements["01"].value, "09506000134352");
+        assert_eq!(data.elements["17"].date.as_deref(), Some("2020-12-31"));
+        assert_eq!(data.elements["10"].value, "ABC123");
+    }
+
+    #[test]
+    fn parses_element_string_with_fnc1() {
+        let text = format!("]C1010950600013435217201231310300015010ABC123{}21XYZ", GS);
+        let data = parse_gs1(&text).unwrap();
+        assert_eq!(data.elements.keys().map(String::as_str).collect::<Vec<&str>>(), ["01", "10", "17", "21", "3103"]);
+        assert_eq!(data.elements["10"].value, "ABC123");
+        assert_eq!(data.elements["21"].value, "XYZ");
+        let weight = data.elements["3103"].decimal.unwrap();
+        assert!((weight - 0.15).abs() < 1e-9);
+    }
+
+    #[test]
+    fn day_zero_means_last_day_of_month() {
+        let data = parse_gs1("(17)240200").unwrap();
+        assert_eq!(data.elements["17"].date.as_deref(), Some("2024-02-29"));
+        assert!(parse_gs1("(17)241301").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_element_strings() {
+        // 校验位错误
+        assert!(parse_gs1("(01)09506000134353").is_err());
+        // AI 重复
+        assert!(parse_gs1("(10)A(10)B").is_err());
+        // 定长 AI 被截断
+        assert!(parse_gs1("01095060001343").is_err());
+        // 未定义的 AI
+        assert!(parse_gs1("(05)123").is_err());
+    }
+
+    #[test]
+    fn parses_digital_link() {
+        let data = parse_digital_link("https://id.gs1.org/01/9506000134352/10/AB%2FC?17=201231&linkType=gs1:pip").unwrap();
+        assert!(data.digital_link);
+        assert_eq!(data.elements.len(), 3);
+        // 省略的前导 0 补齐为 14 位
+        assert_eq!(data.elements["01"].value, "09506000134352");
+        assert_eq!(data.elements["10"].value, "AB/C");
+        assert_eq!(data.elements["17"].date.as_deref(), Some("2020-12-31"));
+        assert!(parse_digital_link("https://id.gs1.org/01/9506000134352/10").is_err());
+
+        // 自定义路径前缀之后的主键
+        let data = parse_digital_link("https://brand.example/products/00/106141412345678908").unwrap();
+        assert_eq!(data.elements["00"].title, "SSCC");
+    }
+
+    #[test]
+    fn ordinary_urls_are_not_digital_links() {
+        let code = |text: &str| CodeInfo {
+            code: text.to_string(),
+            ..Default::default()
+        };
+        for url in [
+            "https://blog.example/archive/10/hello",
+            "https://example.com/21/serial",
+            "https://example.com/page?10=ABC&17=201231",
+            "https://news.example/2024/01/15/story",
+            "http://example.com/",
+        ] {
+            assert!(parse_digital_link(url).is_err(), "{}", url);
+            assert!(parse_code(&code(url)).is_none(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn percent_decode_requires_two_hex_digits() {
+        assert_eq!(percent_decode("A%2fB").unwrap(), "A/B");
+        assert!(percent_decode("%+1").is_err());
+        assert!(percent_decode("%2").is_err());
+    }
+
+    #[test]
+    fn parse_code_ignores_plain_text_and_urls() {
+        let code = |text: &str| CodeInfo {
+            code: text.to_string(),
+            ..Default::default()
+        };
+        assert!(parse_code(&code("https://example.com/index.html")).is_none());
+        assert!(parse_code(&code("hello")).is_none());
+        assert!(parse_code(&code("(01)09506000134352")).is_some_and(|r| r.is_ok()));
+
+        let mut symbol = code("0109506000134352");
+        symbol.symbology_identifier = Some("]d2".to_string());
+        assert!(parse_code(&symbol).is_some_and(|r| r.is_ok()));
+    }
+}
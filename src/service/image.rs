@@ -1,58 +1,169 @@
-use base64::engine::general_purpose::STANDARD;
-use base64::Engine;
 use opencv::core::{Mat, Vector};
 use opencv::imgcodecs;
 use opencv::prelude::*;
 use reqwest::blocking::get;
 use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use crate::service::datauri::decode_image_data;
+use crate::service::limits::{check_dimensions, check_input_size, inspect_image, ImageLimits, LimitError};
+
+// read_gray_mat_from_path 与 read_gray_mat_from_base64 保持原有的 opencv::Error 签名，错误信息转为 message；
+// 需要区分 LimitError、io::Error 等错误类型时使用对应的 *_with_limits 版本
+fn to_opencv_error(e: Box<dyn Error>) -> opencv::Error {
+    opencv::Error::new(0, e.to_string())
+}
 
 pub fn read_gray_mat_from_path(image_path: &str) -> Result<Mat, opencv::Error> {
-    // 从文件路径读取灰度图片
-    let gray_image = imgcodecs::imread(image_path, imgcodecs::IMREAD_GRAYSCALE)?;
-    Ok(gray_image)
+    read_gray_mat_from_path_with_limits(image_path, &ImageLimits::default()).map_err(to_opencv_error)
+}
+
+// 从文件路径读取灰度图片。不直接用 imread：先读入内存检查头部声明的尺寸，再解码
+pub fn read_gray_mat_from_path_with_limits(image_path: &str, limits: &ImageLimits) -> Result<Mat, Box<dyn Error>> {
+    let file = File::open(image_path)?;
+    check_input_size(usize::try_from(file.metadata()?.len()).unwrap_or(usize::MAX), limits)?;
+    read_gray_mat_from_reader_with_limits(file, limits)
 }
 
 pub fn read_gray_mat_from_base64(base64_str: &str) -> Result<Mat, opencv::Error> {
-    // 检测并移除 base64 头部
-    let base64_data = if let Some(header_pos) = base64_str.find(",") {
-        &base64_str[(header_pos + 1)..]  // 跳过 header
-    } else {
-        base64_str  // 没有 header，直接处理
-    };
+    read_gray_mat_from_base64_with_limits(base64_str, &ImageLimits::default()).map_err(to_opencv_error)
+}
 
-    // 使用标准的 Base64 引擎解码
-    let decoded_data = STANDARD.decode(base64_data)
-        .map_err(|_| opencv::Error::new(0, "Base64 解码失败".to_string()))?;
+pub fn read_gray_mat_from_base64_with_limits(base64_str: &str, limits: &ImageLimits) -> Result<Mat, Box<dyn Error>> {
+    // 按输入长度估算解码后的字节数，超限时不再分配缓冲区
+    check_input_size(base64_str.len() / 4 * 3, limits)?;
 
-    // 将解码的数据转换为 OpenCV 的 Vector<u8>
-    let image_vector = Vector::<u8>::from(decoded_data);
+    // 解析 data URI 头部并校验 MIME，兼容 URL-safe、无填充与折行的 base64
+    let decoded_data = decode_image_data(base64_str)?;
 
-    // 从内存中的字节数据读取图像，并将其转换为 Mat
-    let img = imgcodecs::imdecode(&image_vector, imgcodecs::IMREAD_GRAYSCALE)?;
+    decode_gray_mat(&decoded_data, limits)
+}
 
-    // 检查图片是否成功解码
-    if img.empty() {
-        return Err(opencv::Error::new(0, "无法解码为 Mat 图片".to_string()));
+pub fn read_gray_mat_from_url(url: &str) -> Result<Mat, Box<dyn Error>> {
+    read_gray_mat_from_url_with_limits(url, &ImageLimits::default())
+}
+
+pub fn read_gray_mat_from_url_with_limits(url: &str, limits: &ImageLimits) -> Result<Mat, Box<dyn Error>> {
+    let response = get(url)?;
+    if let Some(length) = response.content_length() {
+        check_input_size(usize::try_from(length).unwrap_or(usize::MAX), limits)?;
     }
+    read_gray_mat_from_reader_with_limits(response, limits)
+}
 
-    Ok(img)
+pub fn read_gray_mat_from_bytes(data: &[u8]) -> Result<Mat, Box<dyn Error>> {
+    read_gray_mat_from_bytes_with_limits(data, &ImageLimits::default())
 }
 
-pub fn read_gray_mat_from_url(url: &str) -> Result<Mat, Box<dyn Error>> {
-    // 从 URL 获取图片的二进制数据
-    let response = get(url)?.bytes()?;
+pub fn read_gray_mat_from_bytes_with_limits(data: &[u8], limits: &ImageLimits) -> Result<Mat, Box<dyn Error>> {
+    decode_gray_mat(data, limits)
+}
 
-    // 将 response 的字节数据转为 OpenCV 的 Vector<u8>
-    let image_vector = Vector::<u8>::from(response.to_vec());
+// 从任意数据流（标准输入、管道、HTTP 响应体等）读取图片，无需先写入临时文件
+pub fn read_gray_mat_from_reader(reader: impl Read) -> Result<Mat, Box<dyn Error>> {
+    read_gray_mat_from_reader_with_limits(reader, &ImageLimits::default())
+}
 
-    // 使用 OpenCV 从内存中的二进制数据解码为 Mat 对象，并转换为灰度图像
-    let img = imgcodecs::imdecode(&image_vector, imgcodecs::IMREAD_GRAYSCALE)?;
+pub fn read_gray_mat_from_reader_with_limits(reader: impl Read, limits: &ImageLimits) -> Result<Mat, Box<dyn Error>> {
+    // 数据流长度未知，最多读取 max_input_bytes + 1 字节即可判断是否超限
+    let mut data = Vec::new();
+    reader.take(limits.max_input_bytes as u64 + 1).read_to_end(&mut data)?;
+    check_input_size(data.len(), limits)?;
+
+    decode_gray_mat(&data, limits)
+}
+
+// 先读取头部检查尺寸，再用 OpenCV 从内存中的二进制数据解码为灰度 Mat。
+// image 无法识别格式或读不出尺寸时仍交给 imdecode，解码后再检查尺寸
+fn decode_gray_mat(data: &[u8], limits: &ImageLimits) -> Result<Mat, Box<dyn Error>> {
+    let unrecognized = match inspect_image(data, limits) {
+        Ok(_) => None,
+        Err(LimitError::UnrecognizedFormat(reason)) => Some(reason),
+        Err(e) => return Err(e.into()),
+    };
+
+    // 将字节数据转换为 OpenCV 的 Vector<u8>
+    let image_vector = Vector::<u8>::from_slice(data);
+
+    // 从内存中的字节数据读取图像，并将其转换为 Mat；空数据等输入 imdecode 会直接报错，此时仍报告无法识别格式
+    let img = match imgcodecs::imdecode(&image_vector, imgcodecs::IMREAD_GRAYSCALE) {
+        Ok(img) => img,
+        Err(e) => {
+            return Err(match unrecognized {
+                Some(reason) => LimitError::UnrecognizedFormat(reason).into(),
+                None => e.into(),
+            })
+        }
+    };
 
     // 检查图片是否成功解码
     if img.empty() {
-        return Err(Box::from("无法将图片解码为 Mat 对象"));
+        return Err(match unrecognized {
+            Some(reason) => LimitError::UnrecognizedFormat(reason).into(),
+            None => Box::from("无法将图片解码为 Mat 对象"),
+        });
+    }
+    if unrecognized.is_some() {
+        check_dimensions(img.cols() as u32, img.rows() as u32, limits)?;
     }
 
     Ok(img)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Scalar, CV_8UC1};
+    use std::io::Cursor;
+
+    #[test]
+    fn reader_stops_at_input_limit() {
+        let limits = ImageLimits {
+            max_input_bytes: 1024,
+            ..ImageLimits::default()
+        };
+        // 无限数据流也只读取 max_input_bytes + 1 字节
+        let err = read_gray_mat_from_reader_with_limits(std::io::repeat(0), &limits).unwrap_err();
+        assert_eq!(err.downcast_ref::<LimitError>(), Some(&LimitError::InputTooLarge { size: 1025, max: 1024 }));
+    }
+
+    #[test]
+    fn path_loader_inspects_header_before_decoding() {
+        let path = std::env::temp_dir().join(format!("barcode-detector-{}.png", std::process::id()));
+        std::fs::write(&path, b"not an image").unwrap();
+        let err = read_gray_mat_from_path_with_limits(path.to_str().unwrap(), &ImageLimits::default()).unwrap_err();
+        let message = read_gray_mat_from_path(path.to_str().unwrap()).unwrap_err().message;
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(err.downcast_ref::<LimitError>(), Some(LimitError::UnrecognizedFormat(_))));
+        assert!(message.starts_with("Unrecognized image format"));
+        let err = read_gray_mat_from_path_with_limits("/nonexistent/image.png", &ImageLimits::default()).unwrap_err();
+        assert!(err.downcast_ref::<std::io::Error>().is_some());
+    }
+
+    #[test]
+    fn rejects_unrecognized_bytes() {
+        let err = read_gray_mat_from_bytes(b"not an image").unwrap_err();
+        assert!(matches!(err.downcast_ref::<LimitError>(), Some(LimitError::UnrecognizedFormat(_))));
+        let err = read_gray_mat_from_reader(Cursor::new(Vec::new())).unwrap_err();
+        assert!(matches!(err.downcast_ref::<LimitError>(), Some(LimitError::UnrecognizedFormat(_))));
+    }
+
+    // image 不支持 Sun Raster，读不出头部时先由 OpenCV 解码，再按解码结果检查尺寸
+    #[test]
+    fn formats_without_readable_header_are_checked_after_decoding() {
+        let image = Mat::new_rows_cols_with_default(20, 30, CV_8UC1, Scalar::all(255.0)).unwrap();
+        let mut buffer = Vector::<u8>::new();
+        imgcodecs::imencode(".ras", &image, &mut buffer, &Vector::new()).unwrap();
+        let data = buffer.to_vec();
+        assert!(matches!(inspect_image(&data, &ImageLimits::default()), Err(LimitError::UnrecognizedFormat(_))));
+
+        let decoded = read_gray_mat_from_bytes(&data).unwrap();
+        assert_eq!((decoded.cols(), decoded.rows()), (30, 20));
+        let limits = ImageLimits {
+            max_pixels: 500,
+            ..ImageLimits::default()
+        };
+        let err = read_gray_mat_from_bytes_with_limits(&data, &limits).unwrap_err();
+        assert_eq!(err.downcast_ref::<LimitError>(), Some(&LimitError::TooManyPixels { pixels: 600, max: 500 }));
+    }
+}
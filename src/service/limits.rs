@@ -0,0 +1,157 @@
+use std::fmt;
+use std::io::Cursor;
+use serde::Deserialize;
+
+// 解码不可信图片前的资源限制。imdecode 按图片头部声明的尺寸分配内存，
+// 几十字节的 PNG 就可以声明数十亿像素，因此先只读取头部检查尺寸再解码；
+// image 无法识别的格式（如 Sun Raster、JPEG 2000）只能先解码，再按解码结果的尺寸检查
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ImageLimits {
+    // 压缩后输入数据的最大字节数
+    pub max_input_bytes: usize,
+    pub max_width: u32,
+    pub max_height: u32,
+    pub max_pixels: u64,
+}
+
+impl Default for ImageLimits {
+    fn default() -> Self {
+        Self {
+            max_input_bytes: 50 * 1024 * 1024,
+            max_width: 20_000,
+            max_height: 20_000,
+            // 灰度解码约占 64MB
+            max_pixels: 64_000_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LimitError {
+    InputTooLarge { size: usize, max: usize },
+    DimensionsTooLarge { width: u32, height: u32, max_width: u32, max_height: u32 },
+    TooManyPixels { pixels: u64, max: u64 },
+    // 无法从头部识别格式或读出尺寸，且 imdecode 同样无法解码
+    UnrecognizedFormat(String),
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitError::InputTooLarge { size, max } => write!(f, "Image input of {} bytes exceeds the limit of {} bytes", size, max),
+            LimitError::DimensionsTooLarge { width, height, max_width, max_height } => {
+                write!(f, "Image dimensions {}x{} exceed the limit of {}x{}", width, height, max_width, max_height)
+            }
+            LimitError::TooManyPixels { pixels, max } => write!(f, "Image has {} pixels, exceeding the limit of {}", pixels, max),
+            LimitError::UnrecognizedFormat(reason) => write!(f, "Unrecognized image format: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+pub fn check_input_size(size: usize, limits: &ImageLimits) -> Result<(), LimitError> {
+    if size > limits.max_input_bytes {
+        return Err(LimitError::InputTooLarge { size, max: limits.max_input_bytes });
+    }
+    Ok(())
+}
+
+// 只解析头部得到宽高并检查限制，不解码像素数据
+pub fn inspect_image(bytes: &[u8], limits: &ImageLimits) -> Result<(u32, u32), LimitError> {
+    check_input_size(bytes.len(), limits)?;
+    let (width, height) = image::ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| LimitError::UnrecognizedFormat(e.to_string()))?
+        .into_dimensions()
+        .map_err(|e| LimitError::UnrecognizedFormat(e.to_string()))?;
+    check_dimensions(width, height, limits)?;
+    Ok((width, height))
+}
+
+// 检查宽高与像素总数，用于头部读出的尺寸及无法读取头部时解码结果的尺寸
+pub fn check_dimensions(width: u32, height: u32, limits: &ImageLimits) -> Result<(), LimitError> {
+    if width > limits.max_width || height > limits.max_height {
+        return Err(LimitError::DimensionsTooLarge {
+            width,
+            height,
+            max_width: limits.max_width,
+            max_height: limits.max_height,
+        });
+    }
+    let pixels = width as u64 * height as u64;
+    if pixels > limits.max_pixels {
+        return Err(LimitError::TooManyPixels { pixels, max: limits.max_pixels });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crc32(data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &b in data {
+            crc ^= b as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            }
+        }
+        !crc
+    }
+
+    fn push_chunk(png: &mut Vec<u8>, kind: &[u8], data: &[u8]) {
+        let mut chunk = kind.to_vec();
+        chunk.extend_from_slice(data);
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        png.extend_from_slice(&chunk);
+        png.extend_from_slice(&crc32(&chunk).to_be_bytes());
+    }
+
+    // 声明了宽高（位深 8、灰度）但 IDAT 为空的 PNG，足以读出尺寸
+    fn png_header(width: u32, height: u32) -> Vec<u8> {
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        let mut ihdr = width.to_be_bytes().to_vec();
+        ihdr.extend_from_slice(&height.to_be_bytes());
+        ihdr.extend_from_slice(&[8, 0, 0, 0, 0]);
+        push_chunk(&mut png, b"IHDR", &ihdr);
+        push_chunk(&mut png, b"IDAT", &[]);
+        push_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    #[test]
+    fn reads_dimensions_from_header() {
+        assert_eq!(inspect_image(&png_header(640, 480), &ImageLimits::default()), Ok((640, 480)));
+    }
+
+    #[test]
+    fn rejects_decompression_bomb() {
+        let limits = ImageLimits::default();
+        assert!(matches!(inspect_image(&png_header(100_000, 100_000), &limits), Err(LimitError::DimensionsTooLarge { .. })));
+        assert!(matches!(inspect_image(&png_header(10_000, 10_000), &limits), Err(LimitError::TooManyPixels { .. })));
+    }
+
+    #[test]
+    fn checks_decoded_dimensions() {
+        let limits = ImageLimits {
+            max_pixels: 100,
+            ..ImageLimits::default()
+        };
+        assert_eq!(check_dimensions(10, 10, &limits), Ok(()));
+        assert!(matches!(check_dimensions(10, 11, &limits), Err(LimitError::TooManyPixels { pixels: 110, .. })));
+        assert!(matches!(check_dimensions(20_001, 1, &limits), Err(LimitError::DimensionsTooLarge { .. })));
+    }
+
+    #[test]
+    fn rejects_oversized_and_unknown_input() {
+        let limits = ImageLimits {
+            max_input_bytes: 16,
+            ..ImageLimits::default()
+        };
+        assert!(matches!(inspect_image(&png_header(1, 1), &limits), Err(LimitError::InputTooLarge { .. })));
+        assert!(matches!(inspect_image(b"not an image", &ImageLimits::default()), Err(LimitError::UnrecognizedFormat(_))));
+    }
+}
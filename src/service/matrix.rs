@@ -0,0 +1,347 @@
+use std::collections::HashSet;
+use opencv::core::Mat;
+use opencv::prelude::*;
+use rxing::helpers::detect_multiple_in_luma_with_hints;
+use rxing::pdf417::PDF417RXingResultMetadata;
+use rxing::{BarcodeFormat, DecodeHintType, DecodeHintValue, DecodingHintDictionary, RXingResult, RXingResultMetadataType, RXingResultMetadataValue};
+use crate::basic::Exception;
+use crate::service::charset::{detect_text, identify_charset, is_plain_text};
+use crate::service::dto::{MacroPdf417, Point, StructuredAppend};
+use crate::service::options::{MatrixFormat, MatrixOptions};
+
+// rxing 的解码结果，只保留组装 CodeInfo 需要的部分，rxing 的类型不出本模块
+pub(crate) struct MatrixResult {
+    // 无法无损解码为文本时为空
+    pub(crate) text: String,
+    pub(crate) raw: Vec<u8>,
+    pub(crate) charset: Option<&'static str>,
+    pub(crate) eci: bool,
+    pub(crate) symbology_identifier: Option<String>,
+    pub(crate) category: &'static str,
+    // 顺时针排列的四个角点
+    pub(crate) points: Vec<Point>,
+    // 解码器给出的符号旋转角度（度）
+    pub(crate) rotation: Option<i32>,
+    // 解码器按镜像（从背面或经反射看到的）符号解出，目前只有 QR 码会报告
+    pub(crate) mirrored: bool,
+    pub(crate) error_correction_level: Option<String>,
+    pub(crate) macro_pdf417: Option<MacroPdf417>,
+    pub(crate) structured_append: Option<StructuredAppend>,
+}
+
+fn to_barcode_format(format: MatrixFormat) -> BarcodeFormat {
+    match format {
+        MatrixFormat::DataMatrix => BarcodeFormat::DATA_MATRIX,
+        MatrixFormat::Pdf417 => BarcodeFormat::PDF_417,
+        MatrixFormat::Aztec => BarcodeFormat::AZTEC,
+        MatrixFormat::QrCode => BarcodeFormat::QR_CODE,
+        MatrixFormat::MicroQrCode => BarcodeFormat::MICRO_QR_CODE,
+        MatrixFormat::RectangularMicroQrCode => BarcodeFormat::RECTANGULAR_MICRO_QR_CODE,
+        MatrixFormat::Code128 => BarcodeFormat::CODE_128,
+        MatrixFormat::Code39 => BarcodeFormat::CODE_39,
+        MatrixFormat::Itf => BarcodeFormat::ITF,
+    }
+}
+
+// 码制名称与 OpenCV BarcodeDetector 的类型名保持同一风格
+fn category_name(format: &BarcodeFormat) -> &'static str {
+    match format {
+        BarcodeFormat::DATA_MATRIX => "DATA_MATRIX",
+        BarcodeFormat::PDF_417 => "PDF_417",
+        BarcodeFormat::AZTEC => "AZTEC",
+        BarcodeFormat::QR_CODE => "QR_CODE",
+        BarcodeFormat::MICRO_QR_CODE => "MICRO_QR_CODE",
+        BarcodeFormat::RECTANGULAR_MICRO_QR_CODE => "RMQR_CODE",
+        BarcodeFormat::CODE_128 => "CODE_128",
+        BarcodeFormat::CODE_39 => "CODE_39",
+        BarcodeFormat::ITF => "ITF",
+        _ => "UNKNOWN",
+    }
+}
+
+fn mat_to_luma(gray_image: &Mat) -> opencv::Result<Vec<u8>> {
+    if gray_image.is_continuous() {
+        return Ok(gray_image.data_bytes()?.to_vec());
+    }
+    // ROI 等非连续内存先拷贝成连续的再读取
+    let continuous = gray_image.try_clone()?;
+    Ok(continuous.data_bytes()?.to_vec())
+}
+
+// 将解码器给出的角点整理为顺时针顺序，并从最靠近图像左上角的点开始；不足四个点时取外接矩形
+fn order_corners(points: &[Point]) -> Vec<Point> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+    if points.len() < 4 {
+        let min_x = points.iter().map(|p| p.x).fold(f32::MAX, f32::min);
+        let min_y = points.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+        let max_x = points.iter().map(|p| p.x).fold(f32::MIN, f32::max);
+        let max_y = points.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+        return vec![
+            Point { x: min_x, y: min_y },
+            Point { x: max_x, y: min_y },
+            Point { x: max_x, y: max_y },
+            Point { x: min_x, y: max_y },
+        ];
+    }
+    let mut corners = points[..4].to_vec();
+    let cx = corners.iter().map(|p| p.x).sum::<f32>() / 4.0;
+    let cy = corners.iter().map(|p| p.y).sum::<f32>() / 4.0;
+    // 图像坐标系 y 轴向下，atan2 递增即顺时针
+    corners.sort_by(|a, b| {
+        let angle_a = (a.y - cy).atan2(a.x - cx);
+        let angle_b = (b.y - cy).atan2(b.x - cx);
+        angle_a.total_cmp(&angle_b)
+    });
+    let start = corners
+        .iter()
+        .enumerate()
+        .min_by(|a, b| (a.1.x + a.1.y).total_cmp(&(b.1.x + b.1.y)))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    corners.rotate_left(start);
+    corners
+}
+
+// 符号标识符（如 ]Q2、]d4）的修饰符表明符号是否带有 ECI 字符集指示
+fn has_eci(symbology_identifier: &str) -> bool {
+    let mut chars = symbology_identifier.chars().skip(1);
+    match (chars.next(), chars.next()) {
+        (Some('Q'), Some(m)) => matches!(m, '2' | '4' | '6'),
+        (Some('d'), Some(m)) => matches!(m, '4' | '5' | '6'),
+        (Some('z'), Some(m)) => matches!(m, '3' | '4' | '5'),
+        (Some('L'), Some(m)) => m == '1',
+        _ => false,
+    }
+}
+
+struct Payload {
+    text: String,
+    raw: Vec<u8>,
+    charset: Option<&'static str>,
+    eci: bool,
+    symbology_identifier: Option<String>,
+}
+
+// rxing 只给出解码后的文本与字节模式的字节段。字节段能按某个字符集还原出 rxing 的文本时即信任 rxing：
+// 带 ECI 时 rxing 按指定字符集解码，没有 ECI 时按 MatrixOptions.charset 解码，未设置时在 UTF-8/ISO-8859-1/Shift_JIS 之间选择。
+// 只有按该字符集解码出现控制字符（典型情况是 GBK 等字节被当作 ISO-8859-1 的 0x80-0x9F）时才重新识别字符集。
+// 字节段只包含字节模式的数据，与数字、字母、汉字等模式混排时无法还原完整的原始字节，
+// 此时只保留 rxing 的文本，raw 留空、charset 为 None，不把重新编码的文本冒充为符号中的原始字节
+fn payload(result: &RXingResult) -> Payload {
+    let metadata = result.getRXingResultMetadata();
+    let symbology_identifier = match metadata.get(&RXingResultMetadataType::SYMBOLOGY_IDENTIFIER) {
+        Some(RXingResultMetadataValue::SymbologyIdentifier(identifier)) => Some(identifier.clone()),
+        _ => None,
+    };
+    let segments = match metadata.get(&RXingResultMetadataType::BYTE_SEGMENTS) {
+        Some(RXingResultMetadataValue::ByteSegments(segments)) => segments.concat(),
+        _ => Vec::new(),
+    };
+    payload_from(result.getText(), segments, symbology_identifier)
+}
+
+fn payload_from(text: &str, segments: Vec<u8>, symbology_identifier: Option<String>) -> Payload {
+    let eci = symbology_identifier.as_deref().is_some_and(has_eci);
+    let charset = if segments.is_empty() { None } else { identify_charset(&segments, text) };
+    match charset {
+        Some(charset) if eci || is_plain_text(text) => Payload {
+            text: text.to_string(),
+            raw: segments,
+            charset: Some(charset),
+            eci,
+            symbology_identifier,
+        },
+        Some(_) => {
+            let decoded = detect_text(&segments);
+            Payload {
+                text: decoded.text.unwrap_or_default(),
+                raw: segments,
+                charset: decoded.charset,
+                eci,
+                symbology_identifier,
+            }
+        }
+        None => Payload {
+            text: text.to_string(),
+            raw: Vec::new(),
+            charset: None,
+            eci,
+            symbology_identifier,
+        },
+    }
+}
+
+fn to_macro_pdf417(metadata: &PDF417RXingResultMetadata) -> MacroPdf417 {
+    let non_empty = |s: &str| if s.is_empty() { None } else { Some(s.to_string()) };
+    // rxing 用 -1 表示未携带的可选字段
+    MacroPdf417 {
+        segment_index: metadata.getSegmentIndex(),
+        segment_count: usize::try_from(metadata.getSegmentCount()).ok(),
+        file_id: metadata.getFileId().to_string(),
+        last_segment: metadata.isLastSegment(),
+        file_name: non_empty(metadata.getFileName()),
+        sender: non_empty(metadata.getSender()),
+        addressee: non_empty(metadata.getAddressee()),
+        file_size: Some(metadata.getFileSize()).filter(|&v| v >= 0),
+        timestamp: Some(metadata.getTimestamp()).filter(|&v| v >= 0),
+        checksum: Some(metadata.getChecksum()).filter(|&v| v >= 0),
+    }
+}
+
+fn to_matrix_result(result: &RXingResult) -> MatrixResult {
+    let points = result.getPoints().iter().map(|p| Point { x: p.x, y: p.y }).collect::<Vec<Point>>();
+    let metadata = result.getRXingResultMetadata();
+    let rotation = match metadata.get(&RXingResultMetadataType::ORIENTATION) {
+        Some(RXingResultMetadataValue::Orientation(degrees)) => Some(*degrees),
+        _ => None,
+    };
+    let mirrored = matches!(metadata.get(&RXingResultMetadataType::IS_MIRRORED), Some(RXingResultMetadataValue::IsMirrored(true)));
+    let error_correction_level = match metadata.get(&RXingResultMetadataType::ERROR_CORRECTION_LEVEL) {
+        Some(RXingResultMetadataValue::ErrorCorrectionLevel(level)) => Some(level.clone()),
+        _ => None,
+    };
+    let macro_pdf417 = match metadata.get(&RXingResultMetadataType::PDF417_EXTRA_METADATA) {
+        Some(RXingResultMetadataValue::Pdf417ExtraMetadata(extra)) => Some(to_macro_pdf417(extra)),
+        _ => None,
+    };
+    // 序列号高 4 位为符号序号（从 0 开始），低 4 位为符号总数减 1；没有 Structured Append 时为负数或不存在
+    let sequence = match metadata.get(&RXingResultMetadataType::STRUCTURED_APPEND_SEQUENCE) {
+        Some(RXingResultMetadataValue::StructuredAppendSequence(sequence)) if *sequence >= 0 => Some(*sequence),
+        _ => None,
+    };
+    let parity = match metadata.get(&RXingResultMetadataType::STRUCTURED_APPEND_PARITY) {
+        Some(RXingResultMetadataValue::StructuredAppendParity(parity)) if *parity >= 0 => Some(*parity),
+        _ => None,
+    };
+    let structured_append = sequence.map(|sequence| StructuredAppend {
+        index: ((sequence >> 4) & 0x0F) as u8,
+        total: ((sequence & 0x0F) + 1) as u8,
+        parity: parity.unwrap_or(0) as u8,
+    });
+    let payload = payload(result);
+    MatrixResult {
+        text: payload.text,
+        raw: payload.raw,
+        charset: payload.charset,
+        eci: payload.eci,
+        symbology_identifier: payload.symbology_identifier,
+        category: category_name(result.getBarcodeFormat()),
+        points: order_corners(&points),
+        rotation,
+        mirrored,
+        error_correction_level,
+        macro_pdf417,
+        structured_append,
+    }
+}
+
+fn hints_for(formats: HashSet<BarcodeFormat>, options: &MatrixOptions) -> DecodingHintDictionary {
+    let mut hints = DecodingHintDictionary::new();
+    hints.insert(DecodeHintType::POSSIBLE_FORMATS, DecodeHintValue::PossibleFormats(formats));
+    if options.try_harder {
+        hints.insert(DecodeHintType::TRY_HARDER, DecodeHintValue::TryHarder(true));
+    }
+    if let Some(charset) = &options.charset {
+        hints.insert(DecodeHintType::CHARACTER_SET, DecodeHintValue::CharacterSet(charset.clone()));
+    }
+    hints
+}
+
+fn read_luma(gray_image: &Mat) -> Result<Vec<u8>, Exception> {
+    mat_to_luma(gray_image).map_err(|e| Exception::new(0, format!("Failed to read image data: {}", e)))
+}
+
+fn decode_formats(gray_image: &Mat, formats: &[MatrixFormat], options: &MatrixOptions) -> Result<Vec<MatrixResult>, Exception> {
+    if formats.is_empty() || gray_image.empty() {
+        return Ok(Vec::new());
+    }
+    let luma = read_luma(gray_image)?;
+    let mut hints = hints_for(formats.iter().map(|f| to_barcode_format(*f)).collect(), options);
+
+    // rxing 在没有找到或无法纠错时返回错误，统一视为没有结果
+    let results = detect_multiple_in_luma_with_hints(luma, gray_image.cols() as u32, gray_image.rows() as u32, &mut hints).unwrap_or_default();
+    Ok(results.iter().map(to_matrix_result).collect())
+}
+
+// rxing 整图识别一维码时只给出扫描线两端的点，无法推导条码区域，一维码只在 decode_matrix_region 中解码。
+// 逐个码制识别，每个码制开始前调用 should_stop，返回 true 时停止并返回已识别的结果
+pub(crate) fn detect_and_decode_matrix(gray_image: &Mat, options: &MatrixOptions, should_stop: impl Fn() -> bool) -> Result<Vec<MatrixResult>, Exception> {
+    let formats = options.formats.iter().copied().filter(|f| !f.is_linear()).collect::<Vec<MatrixFormat>>();
+    if formats.is_empty() || gray_image.empty() {
+        return Ok(Vec::new());
+    }
+    let luma = read_luma(gray_image)?;
+    let mut results = Vec::new();
+    for format in formats {
+        if should_stop() {
+            break;
+        }
+        let mut hints = hints_for(HashSet::from([to_barcode_format(format)]), options);
+        let decoded = detect_multiple_in_luma_with_hints(luma.clone(), gray_image.cols() as u32, gray_image.rows() as u32, &mut hints).unwrap_or_default();
+        results.extend(decoded.iter().map(to_matrix_result));
+    }
+    Ok(results)
+}
+
+// 在已裁切摆正的区域内解码，用于 OpenCV 检出但无法解码的区域（PDF417 及 EAN/UPC 以外的一维码）
+pub(crate) fn decode_matrix_region(code_image: &Mat, formats: &[MatrixFormat], options: &MatrixOptions) -> Result<Option<MatrixResult>, Exception> {
+    Ok(decode_formats(code_image, formats, options)?.into_iter().next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_corners_clockwise_from_top_left() {
+        let points = [Point { x: 10.0, y: 10.0 }, Point { x: 0.0, y: 10.0 }, Point { x: 10.0, y: 0.0 }, Point { x: 0.0, y: 0.0 }];
+        let ordered = order_corners(&points).iter().map(|p| (p.x, p.y)).collect::<Vec<(f32, f32)>>();
+        assert_eq!(ordered, vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]);
+    }
+
+    #[test]
+    fn aztec_maps_to_rxing_and_back() {
+        let format = serde_json::from_str::<MatrixFormat>("\"AZTEC\"").unwrap();
+        assert_eq!(format, MatrixFormat::Aztec);
+        assert_eq!(category_name(&to_barcode_format(format)), "AZTEC");
+        assert!(MatrixOptions::default().formats.contains(&MatrixFormat::Aztec));
+    }
+
+    #[test]
+    fn qr_variants_keep_distinct_categories() {
+        let formats = serde_json::from_str::<Vec<MatrixFormat>>(r#"["QR_CODE", "MICRO_QR_CODE", "RMQR_CODE"]"#).unwrap();
+        let categories = formats.iter().map(|&f| category_name(&to_barcode_format(f))).collect::<Vec<&str>>();
+        assert_eq!(categories, vec!["QR_CODE", "MICRO_QR_CODE", "RMQR_CODE"]);
+        assert!(formats.iter().all(|f| MatrixOptions::default().formats.contains(f) && !f.is_linear()));
+    }
+
+    #[test]
+    fn trusts_rxing_text_unless_bytes_are_not_text_in_its_charset() {
+        // 合法的 ISO-8859-1 文本不再被 chardetng 改判为其他字符集
+        let payload = payload_from("Café crème", "Café crème".chars().map(|c| c as u8).collect(), Some("]Q1".to_string()));
+        assert_eq!((payload.text.as_str(), payload.charset), ("Café crème", Some("ISO-8859-1")));
+
+        // GBK 字节被 rxing 当作 ISO-8859-1 解码时出现 C1 控制字符，按原始字节重新识别
+        let (gbk, _, _) = encoding_rs::GBK.encode("順豐速運單號，請於門市簽收");
+        let latin1 = gbk.iter().map(|&b| b as char).collect::<String>();
+        let payload = payload_from(&latin1, gbk.to_vec(), Some("]Q1".to_string()));
+        assert_eq!((payload.text.as_str(), payload.charset), ("順豐速運單號，請於門市簽收", Some("GBK")));
+
+        // 带 ECI 时始终采用 rxing 按指定字符集解码的文本
+        let payload = payload_from("Ab", b"Ab".to_vec(), Some("]Q2".to_string()));
+        assert!(payload.eci);
+        assert_eq!(payload.raw, b"Ab");
+
+        // 字节段与其他模式混排时字节段不能还原文本，raw 留空
+        let payload = payload_from("12345abc", b"abc".to_vec(), Some("]Q1".to_string()));
+        assert_eq!((payload.text.as_str(), payload.raw.len(), payload.charset), ("12345abc", 0, None));
+    }
+
+    #[test]
+    fn nan_corners_do_not_panic() {
+        let points = [Point { x: f32::NAN, y: 0.0 }, Point { x: 10.0, y: 0.0 }, Point { x: 10.0, y: f32::NAN }, Point { x: 0.0, y: 10.0 }];
+        assert_eq!(order_corners(&points).len(), 4);
+    }
+}
@@ -1,3 +1,19 @@
+pub mod aamva;
 pub mod barcode;
-mod dto;
-pub(crate) mod image;
+mod calendar;
+mod charset;
+pub mod content;
+pub mod datauri;
+pub mod dto;
+pub mod encoder;
+mod geometry;
+pub mod gs1;
+pub mod image;
+pub mod limits;
+mod matrix;
+pub mod options;
+pub mod structured;
+pub mod superres;
+pub mod synthetic;
+mod upcean;
+pub mod waybill;
@@ -0,0 +1,243 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use serde::Deserialize;
+use crate::service::waybill::WaybillRules;
+
+// 裁切时在某一方向上向外扩展的边距
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "unit", content = "value", rename_all = "snake_case")]
+pub enum Margin {
+    // 绝对像素
+    Pixels(f32),
+    // 模块宽度的倍数，无法估计模块宽度时按条码长边约 100 个模块折算
+    Modules(f32),
+    // 检测框在该方向上边长的比例
+    Ratio(f32),
+}
+
+impl Margin {
+    // 换算成像素；length 为检测框在该方向上的边长
+    pub(crate) fn to_pixels(self, length: f32, module_width: Option<f32>, long_side: f32) -> f32 {
+        let pixels = match self {
+            Margin::Pixels(p) => p,
+            Margin::Modules(m) => m * module_width.unwrap_or(long_side / 100.0),
+            Margin::Ratio(r) => r * length,
+        };
+        pixels.max(0.0)
+    }
+}
+
+// extract_and_expand 的扩展参数，horizontal 沿阅读方向（条码长边），vertical 沿条高方向
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ExpandOptions {
+    pub horizontal: Margin,
+    pub vertical: Margin,
+    // 首次解码失败后的重试次数
+    pub retries: u32,
+    // 每次重试时边距的放大倍数
+    pub growth: f32,
+}
+
+impl Default for ExpandOptions {
+    fn default() -> Self {
+        Self {
+            // 一维码标准静区至少 10 个模块
+            horizontal: Margin::Modules(10.0),
+            // 上下各扩展条高的 50%，与之前的固定行为一致
+            vertical: Margin::Ratio(0.5),
+            retries: 2,
+            growth: 2.0,
+        }
+    }
+}
+
+// OpenCV dnn_superres 超分辨率模型，需要启用 superres 特性
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(not(feature = "superres"), allow(dead_code))]
+pub struct SuperResolution {
+    // 模型文件路径，如 ESPCN_x4.pb
+    pub model_path: String,
+    // 模型算法：espcn、fsrcnn、lapsrn、edsr
+    pub algorithm: String,
+    // 模型的放大倍数，需与模型文件一致
+    pub scale: i32,
+    // 模块宽度（像素）小于该值时才使用超分辨率
+    #[serde(default = "default_super_resolution_below")]
+    pub below_module_width: f32,
+}
+
+fn default_super_resolution_below() -> f32 {
+    2.0
+}
+
+// enhance_vertical_lines_with_scaling 的放大参数
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ScaleOptions {
+    // 放大后每个模块期望达到的像素数；增强时的闭运算核宽 15 像素，该值应大于核宽
+    pub target_pixels_per_module: f32,
+    pub max_scale: f32,
+    // 放大后图像的最大像素数
+    pub max_pixels: u64,
+    pub super_resolution: Option<SuperResolution>,
+}
+
+impl Default for ScaleOptions {
+    fn default() -> Self {
+        Self {
+            target_pixels_per_module: 20.0,
+            max_scale: 10.0,
+            max_pixels: 8_000_000,
+            super_resolution: None,
+        }
+    }
+}
+
+// BarcodeDetector 的构造参数，未设置的项保持 OpenCV 默认值
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DetectorOptions {
+    // 超分辨率模型 sr.prototxt 与 sr.caffemodel 的本地路径，需同时提供
+    pub sr_prototxt: Option<String>,
+    pub sr_model: Option<String>,
+    // 图像短边超过该值时先缩小再检测
+    pub downsampling_threshold: Option<f64>,
+    // 检测窗口相对图像短边的比例
+    pub detector_scales: Option<Vec<f32>>,
+    // 梯度幅值阈值，调低可检出低对比度条码
+    pub gradient_threshold: Option<f64>,
+}
+
+// OpenCV BarcodeDetector 不支持、改由 rxing 解码的码制
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MatrixFormat {
+    DataMatrix,
+    #[serde(rename = "PDF_417")]
+    Pdf417,
+    Aztec,
+    QrCode,
+    MicroQrCode,
+    // 长方形 Micro QR（rMQR）
+    #[serde(rename = "RMQR_CODE")]
+    RectangularMicroQrCode,
+    // OpenCV 只能解码 EAN/UPC，其余一维码在 OpenCV 检出的区域内交给 rxing
+    #[serde(rename = "CODE_128")]
+    Code128,
+    #[serde(rename = "CODE_39")]
+    Code39,
+    Itf,
+}
+
+impl MatrixFormat {
+    // 一维码只在 OpenCV 检出的区域内解码，不参与 rxing 整图识别
+    pub fn is_linear(self) -> bool {
+        matches!(self, MatrixFormat::Code128 | MatrixFormat::Code39 | MatrixFormat::Itf)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MatrixOptions {
+    // 需要识别的码制，为空时跳过
+    pub formats: Vec<MatrixFormat>,
+    // 以更多耗时换取更高的识别率
+    pub try_harder: bool,
+    // 没有 ECI 时字节段采用的字符集，如 "GBK"。为空时由 rxing 在 UTF-8/ISO-8859-1/Shift_JIS 之间判断，
+    // 只有按其结果解码出控制字符时才重新识别；GBK 汉字的字节通常也是合法的 ISO-8859-1 或 Shift_JIS，
+    // 供应商标签固定使用 GBK 且不带 ECI 时应在此指定
+    pub charset: Option<String>,
+}
+
+impl Default for MatrixOptions {
+    fn default() -> Self {
+        Self {
+            formats: vec![
+                MatrixFormat::DataMatrix,
+                MatrixFormat::Pdf417,
+                MatrixFormat::Aztec,
+                MatrixFormat::QrCode,
+                MatrixFormat::MicroQrCode,
+                MatrixFormat::RectangularMicroQrCode,
+                MatrixFormat::Code128,
+                MatrixFormat::Code39,
+                MatrixFormat::Itf,
+            ],
+            try_harder: true,
+            charset: None,
+        }
+    }
+}
+
+// 取消标记，克隆后共享同一状态：在其他线程调用 cancel 后，识别流程在下一个检查点停止并返回已有结果
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DecodeOptions {
+    pub detector: DetectorOptions,
+    pub expand: ExpandOptions,
+    pub scale: ScaleOptions,
+    pub matrix: MatrixOptions,
+    // 在结果中附带各处理阶段的耗时，用于线上性能分析
+    pub profile: bool,
+    // 单次识别的时间预算（毫秒），在检测前、每个区域、每次重试、放大增强与解码之间、rxing 区域解码前
+    // 及 rxing 整图识别的每个码制之前检查，超时后返回已识别的结果。单个阶段（如放大增强、一个码制的整图识别）
+    // 开始后不会被打断，实际耗时可能略超预算；所有工作已完成时不会记为超时
+    pub timeout_ms: Option<u64>,
+    // 与 timeout_ms 在同样的检查点生效，命令行批量识别时由 --batch-timeout-ms 的后台线程触发
+    #[serde(skip)]
+    pub cancellation: Option<CancellationToken>,
+    // 一维码内容的运单号识别规则，默认为内置规则，可追加自定义规则
+    #[serde(skip)]
+    pub waybill_rules: WaybillRules,
+    // 像素数超过该值的图像先等比缩小再检测，一维码区域换算回原图后在原图上解码，二维码在缩小后的图像上识别；
+    // 结果中的坐标均为原图坐标，为 None 时不缩小
+    pub max_detect_pixels: Option<u64>,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            detector: DetectorOptions::default(),
+            expand: ExpandOptions::default(),
+            scale: ScaleOptions::default(),
+            matrix: MatrixOptions::default(),
+            profile: false,
+            timeout_ms: None,
+            cancellation: None,
+            waybill_rules: WaybillRules::default(),
+            // 约 2500 万像素，常见手机照片不受影响
+            max_detect_pixels: Some(25_000_000),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detector_options_keep_opencv_defaults_unless_set() {
+        let options = serde_json::from_str::<DecodeOptions>(r#"{"detector": {"gradient_threshold": 32.0, "detector_scales": [0.01, 0.03]}}"#).unwrap();
+        assert_eq!(options.detector.gradient_threshold, Some(32.0));
+        assert_eq!(options.detector.detector_scales, Some(vec![0.01, 0.03]));
+        assert_eq!((options.detector.sr_prototxt, options.detector.sr_model, options.detector.downsampling_threshold), (None, None, None));
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use crate::service::charset::{decode_with, detect_text};
+use crate::service::dto::{CodeInfo, StructuredMessage, StructuredPart};
+
+// 同一序号有多个候选符号时最多尝试的组合数，避免大量冲突时组合爆炸
+const MAX_COMBINATIONS: usize = 256;
+
+fn xor(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |acc, b| acc ^ b)
+}
+
+// 按序号逐一选取候选符号，优先选出全部数据字节的异或等于 parity 的组合。
+// 返回选中的各部分，以及 parity 是否校验通过：缺少符号或有符号无法还原原始字节时无法校验，为 None
+fn select_parts<'a>(parity: u8, total: u8, candidates: &BTreeMap<u8, Vec<&'a CodeInfo>>) -> (BTreeMap<u8, &'a CodeInfo>, Option<bool>) {
+    let first = candidates.iter().map(|(&index, c)| (index, c[0])).collect::<BTreeMap<u8, &CodeInfo>>();
+    let verifiable = first.len() == total as usize && candidates.values().flatten().all(|c| !c.raw.is_empty());
+    if !verifiable {
+        return (first, None);
+    }
+
+    let lists = candidates.values().collect::<Vec<&Vec<&CodeInfo>>>();
+    let combinations = lists.iter().map(|c| c.len()).product::<usize>();
+    // 按混合进制逐个枚举组合，choice[i] 为第 i 个序号选中的候选
+    let mut choice = vec![0usize; lists.len()];
+    for _ in 0..combinations.min(MAX_COMBINATIONS) {
+        let parts = lists.iter().zip(&choice).map(|(c, &i)| c[i]).collect::<Vec<&CodeInfo>>();
+        if parts.iter().fold(0, |acc, c| acc ^ xor(&c.raw)) == parity {
+            let selected = candidates.keys().copied().zip(parts).collect::<BTreeMap<u8, &CodeInfo>>();
+            return (selected, Some(true));
+        }
+        for (digit, list) in choice.iter_mut().zip(&lists) {
+            *digit += 1;
+            if *digit < list.len() {
+                break;
+            }
+            *digit = 0;
+        }
+    }
+    (first, Some(false))
+}
+
+// 将带有 Structured Append 信息的符号按 (parity, total) 分组并按序号拼接。
+// codes 可以来自同一张图片，也可以是批量处理多张图片的结果合集。
+// 不相关的两组符号可能恰好有相同的 parity 与 total，因此集齐后按数据字节的异或校验 parity
+pub fn reassemble_structured_append(codes: &[CodeInfo]) -> Vec<StructuredMessage> {
+    let mut groups = BTreeMap::<(u8, u8), BTreeMap<u8, Vec<&CodeInfo>>>::new();
+    for code in codes {
+        if let Some(sa) = &code.structured_append {
+            let candidates = groups.entry((sa.parity, sa.total)).or_default().entry(sa.index).or_default();
+            // 同一符号被多次识别时只保留第一次的结果
+            if !candidates.iter().any(|c| c.raw == code.raw && c.code == code.code) {
+                candidates.push(code);
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|((parity, total), candidates)| {
+            let (parts, parity_valid) = select_parts(parity, total, &candidates);
+            let missing = (0..total).filter(|i| !parts.contains_key(i)).collect::<Vec<u8>>();
+            let mut raw = parts.values().flat_map(|c| c.raw.iter().copied()).collect::<Vec<u8>>();
+            // 各符号带 ECI 且字符集相同时先拼接原始字节再按该字符集整体解码，跨符号拆分的多字节字符才能还原；
+            // 字符集不同时只能拼接各符号的文本；有符号无法还原原始字节时同样只拼接文本，raw 留空；
+            // 否则按拼接后的字节整体识别字符集
+            let first_charset = parts.values().next().and_then(|c| c.charset.clone());
+            let eci = parts.values().all(|c| c.eci && c.charset.is_some());
+            let same_charset = parts.values().all(|c| c.charset == first_charset && !c.raw.is_empty());
+            let (code, charset) = if let Some(text) = first_charset.as_deref().filter(|_| eci && same_charset).and_then(|charset| decode_with(&raw, charset)) {
+                (text, first_charset)
+            } else if eci {
+                (parts.values().map(|c| c.code.as_str()).collect::<String>(), first_charset.filter(|_| same_charset))
+            } else if parts.values().any(|c| c.raw.is_empty() && !c.code.is_empty()) {
+                raw.clear();
+                (parts.values().map(|c| c.code.as_str()).collect::<String>(), None)
+            } else {
+                let decoded = detect_text(&raw);
+                (decoded.text.unwrap_or_default(), decoded.charset.map(|c| c.to_string()))
+            };
+            StructuredMessage {
+                parity,
+                total,
+                complete: missing.is_empty(),
+                missing,
+                parity_valid,
+                parts: parts
+                    .into_iter()
+                    .map(|(index, c)| StructuredPart {
+                        index,
+                        code: c.code.clone(),
+                    })
+                    .collect(),
+                code,
+                raw,
+                charset,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::dto::StructuredAppend;
+
+    fn part(text: &str, index: u8, total: u8, parity: u8) -> CodeInfo {
+        CodeInfo {
+            code: text.to_string(),
+            raw: text.as_bytes().to_vec(),
+            charset: Some("UTF-8".to_string()),
+            structured_append: Some(StructuredAppend { index, total, parity }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reassembles_and_verifies_parity() {
+        let parity = xor(b"Hello, world");
+        let codes = [part("world", 1, 2, parity), part("Hello, ", 0, 2, parity), part("Hello, ", 0, 2, parity)];
+        let messages = reassemble_structured_append(&codes);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].code, "Hello, world");
+        assert!(messages[0].complete);
+        assert_eq!(messages[0].parity_valid, Some(true));
+        assert_eq!(messages[0].parts.len(), 2);
+    }
+
+    #[test]
+    fn skips_colliding_foreign_part() {
+        let parity = xor(b"Hello, world");
+        // 另一组序列的第 1 个符号恰好有相同的 parity 与 total，且先被识别
+        let codes = [part("there", 1, 2, parity), part("Hello, ", 0, 2, parity), part("world", 1, 2, parity)];
+        let messages = reassemble_structured_append(&codes);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].code, "Hello, world");
+        assert_eq!(messages[0].parity_valid, Some(true));
+    }
+
+    #[test]
+    fn flags_parity_mismatch_and_unverifiable_groups() {
+        let parity = xor(b"Hello, world");
+        let messages = reassemble_structured_append(&[part("Hello, ", 0, 2, parity), part("there", 1, 2, parity)]);
+        assert_eq!(messages[0].parity_valid, Some(false));
+
+        let messages = reassemble_structured_append(&[part("Hello, ", 0, 2, parity)]);
+        assert_eq!((messages[0].complete, messages[0].parity_valid, messages[0].missing.clone()), (false, None, vec![1]));
+
+        // 无法还原原始字节的符号只拼接文本，不做校验
+        let mut mixed = part("world", 1, 2, parity);
+        mixed.raw.clear();
+        mixed.charset = None;
+        let messages = reassemble_structured_append(&[part("Hello, ", 0, 2, parity), mixed]);
+        assert_eq!((messages[0].code.as_str(), messages[0].parity_valid), ("Hello, world", None));
+        assert!(messages[0].raw.is_empty());
+    }
+
+    #[test]
+    fn eci_parts_are_joined_as_bytes_before_decoding() {
+        // “中”的 3 个 UTF-8 字节被拆到两个符号中，单独解码的文本都带有替换字符
+        let bytes = "中文".as_bytes();
+        let parity = xor(bytes);
+        let eci_part = |raw: &[u8], index: u8| CodeInfo {
+            code: String::from_utf8_lossy(raw).into_owned(),
+            raw: raw.to_vec(),
+            charset: Some("UTF-8".to_string()),
+            eci: true,
+            structured_append: Some(StructuredAppend { index, total: 2, parity }),
+            ..Default::default()
+        };
+        let messages = reassemble_structured_append(&[eci_part(&bytes[..2], 0), eci_part(&bytes[2..], 1)]);
+        assert_eq!(messages[0].code, "中文");
+        assert_eq!(messages[0].raw, bytes);
+        assert_eq!(messages[0].charset.as_deref(), Some("UTF-8"));
+    }
+}
@@ -0,0 +1,54 @@
+use opencv::core::Mat;
+use crate::basic::Exception;
+use crate::service::options::SuperResolution;
+
+#[cfg(feature = "superres")]
+use opencv::core::Ptr;
+#[cfg(feature = "superres")]
+use opencv::dnn_superres::{DnnSuperResImpl, DnnSuperResImplTrait};
+#[cfg(feature = "superres")]
+use opencv::imgproc::{cvt_color, COLOR_BGR2GRAY, COLOR_GRAY2BGR};
+
+// 对 OpenCV dnn_superres 的封装，模型在一次检测中只加载一次
+pub struct SuperResolver {
+    #[cfg(feature = "superres")]
+    inner: Ptr<DnnSuperResImpl>,
+    pub(crate) scale: i32,
+    pub(crate) below_module_width: f32,
+}
+
+impl SuperResolver {
+    #[cfg(feature = "superres")]
+    pub fn new(options: &SuperResolution) -> Result<Self, Exception> {
+        let mut inner = DnnSuperResImpl::create().map_err(|e| Exception::new(0, format!("Failed to create super resolution: {}", e)))?;
+        inner.read_model(&options.model_path).map_err(|e| Exception::new(0, format!("Failed to read super resolution model: {}", e)))?;
+        inner.set_model(&options.algorithm, options.scale).map_err(|e| Exception::new(0, format!("Failed to set super resolution model: {}", e)))?;
+        Ok(Self {
+            inner,
+            scale: options.scale,
+            below_module_width: options.below_module_width,
+        })
+    }
+
+    #[cfg(not(feature = "superres"))]
+    pub fn new(_options: &SuperResolution) -> Result<Self, Exception> {
+        Err(Exception::new(0, "Super resolution requires the `superres` feature"))
+    }
+
+    // 模型要求三通道输入，灰度图先转 BGR，放大后再转回灰度
+    #[cfg(feature = "superres")]
+    pub fn upsample(&mut self, gray_image: &Mat) -> opencv::Result<Mat> {
+        let mut bgr = Mat::default();
+        cvt_color(gray_image, &mut bgr, COLOR_GRAY2BGR, 0)?;
+        let mut upsampled = Mat::default();
+        self.inner.upsample(&bgr, &mut upsampled)?;
+        let mut gray = Mat::default();
+        cvt_color(&upsampled, &mut gray, COLOR_BGR2GRAY, 0)?;
+        Ok(gray)
+    }
+
+    #[cfg(not(feature = "superres"))]
+    pub fn upsample(&mut self, _gray_image: &Mat) -> opencv::Result<Mat> {
+        Err(opencv::Error::new(0, "Super resolution requires the `superres` feature".to_string()))
+    }
+}
@@ -0,0 +1,360 @@
+use opencv::core::{Mat, Point2f, Rect, Scalar, Size, Vector, BORDER_CONSTANT};
+use opencv::imgcodecs;
+use opencv::imgproc;
+use opencv::prelude::*;
+use serde::Serialize;
+use crate::basic::Exception;
+use crate::service::barcode::detect_and_decode_with_options;
+use crate::service::dto::CodeInfo;
+use crate::service::encoder::{encode, render_mat, RenderOptions, Symbology};
+use crate::service::options::DecodeOptions;
+use crate::service::upcean::gs1_check_digit;
+
+// 合成测试集：生成各码制的条码图片，施加可控的失真后回读，统计各失真程度下的识别率与误读率，
+// 用于衡量 barcode.rs 中预处理改动的效果
+
+// 可复现的伪随机数（xorshift64*），同一种子生成同样的测试集与失真
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // 种子为 0 时 xorshift 会一直输出 0
+        Self(seed ^ 0x9E37_79B9_7F4A_7C15)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // [0, 1) 均匀分布
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    // Box-Muller 变换得到标准正态分布
+    pub fn next_gaussian(&mut self) -> f64 {
+        let u = self.next_f64().max(f64::MIN_POSITIVE);
+        let v = self.next_f64();
+        (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+    }
+
+    fn digits(&mut self, n: usize) -> String {
+        (0..n).map(|_| (b'0' + self.below(10) as u8) as char).collect()
+    }
+
+    fn chars(&mut self, alphabet: &[u8], n: usize) -> String {
+        (0..n).map(|_| alphabet[self.below(alphabet.len())] as char).collect()
+    }
+}
+
+// 按码制生成随机但合法的内容
+pub fn random_data(symbology: Symbology, rng: &mut Rng) -> String {
+    const TEXT: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-./:";
+    match symbology {
+        Symbology::Ean13 => {
+            // 首位避开 0，否则与 UPC-A 无法区分
+            let body = format!("{}{}", 1 + rng.below(9), rng.digits(11));
+            let digits = body.bytes().map(|b| b - b'0').collect::<Vec<u8>>();
+            format!("{}{}", body, gs1_check_digit(&digits))
+        }
+        Symbology::UpcA => {
+            let body = rng.digits(11);
+            let digits = body.bytes().map(|b| b - b'0').collect::<Vec<u8>>();
+            format!("{}{}", body, gs1_check_digit(&digits))
+        }
+        Symbology::Code128 => {
+            let n = 6 + rng.below(10);
+            rng.chars(TEXT, n)
+        }
+        Symbology::Code39 => {
+            let n = 4 + rng.below(8);
+            rng.chars(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-.", n)
+        }
+        Symbology::Itf => {
+            let n = 2 * (3 + rng.below(5));
+            rng.digits(n)
+        }
+        Symbology::QrCode => {
+            let n = 10 + rng.below(60);
+            format!("https://example.com/{}", rng.chars(TEXT, n))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distortion {
+    None,
+    // 旋转角度（度），画布随之扩大
+    Rotation(f64),
+    // 梯形透视，上边两端各向内收缩宽度的该比例
+    Perspective(f64),
+    // 高斯模糊的 sigma（像素）
+    Blur(f64),
+    // 高斯噪声的标准差（灰度级）
+    Noise(f64),
+    // JPEG 压缩质量 1~100
+    Jpeg(i32),
+    // 对比度，1 为原图，以中灰为中心压缩灰度范围
+    Contrast(f64),
+    // 图像中央被白色贴纸遮挡的宽度比例
+    Occlusion(f64),
+}
+
+impl Distortion {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Distortion::None => "none",
+            Distortion::Rotation(_) => "rotation",
+            Distortion::Perspective(_) => "perspective",
+            Distortion::Blur(_) => "blur",
+            Distortion::Noise(_) => "noise",
+            Distortion::Jpeg(_) => "jpeg",
+            Distortion::Contrast(_) => "contrast",
+            Distortion::Occlusion(_) => "occlusion",
+        }
+    }
+
+    pub fn level(&self) -> f64 {
+        match *self {
+            Distortion::None => 0.0,
+            Distortion::Rotation(v) | Distortion::Perspective(v) | Distortion::Blur(v) | Distortion::Noise(v) | Distortion::Contrast(v) | Distortion::Occlusion(v) => v,
+            Distortion::Jpeg(q) => q as f64,
+        }
+    }
+
+    // 每种失真由轻到重的默认档位
+    pub fn standard_levels() -> Vec<Distortion> {
+        let mut levels = vec![Distortion::None];
+        levels.extend([5.0, 15.0, 30.0, 45.0, 90.0].map(Distortion::Rotation));
+        levels.extend([0.05, 0.1, 0.2, 0.3].map(Distortion::Perspective));
+        levels.extend([0.5, 1.0, 1.5, 2.5].map(Distortion::Blur));
+        levels.extend([5.0, 15.0, 30.0, 50.0].map(Distortion::Noise));
+        levels.extend([90, 50, 20, 10].map(Distortion::Jpeg));
+        levels.extend([0.5, 0.3, 0.2, 0.1].map(Distortion::Contrast));
+        levels.extend([0.02, 0.05, 0.1, 0.2].map(Distortion::Occlusion));
+        levels
+    }
+}
+
+fn cv(e: opencv::Error) -> Exception {
+    Exception::new(0, format!("Failed to distort image: {}", e))
+}
+
+const WHITE: f64 = 255.0;
+
+fn rotate(image: &Mat, degrees: f64) -> opencv::Result<Mat> {
+    let (w, h) = (image.cols() as f64, image.rows() as f64);
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (new_w, new_h) = ((w * cos.abs() + h * sin.abs()).ceil(), (w * sin.abs() + h * cos.abs()).ceil());
+    let mut matrix = imgproc::get_rotation_matrix_2d(Point2f::new((w / 2.0) as f32, (h / 2.0) as f32), degrees, 1.0)?;
+    // 平移到扩大后画布的中心，保证旋转后不被裁切
+    *matrix.at_2d_mut::<f64>(0, 2)? += (new_w - w) / 2.0;
+    *matrix.at_2d_mut::<f64>(1, 2)? += (new_h - h) / 2.0;
+    let mut rotated = Mat::default();
+    imgproc::warp_affine(image, &mut rotated, &matrix, Size::new(new_w as i32, new_h as i32), imgproc::INTER_LINEAR, BORDER_CONSTANT, Scalar::all(WHITE))?;
+    Ok(rotated)
+}
+
+fn perspective(image: &Mat, ratio: f64) -> opencv::Result<Mat> {
+    let (w, h) = (image.cols() as f32, image.rows() as f32);
+    let inset = w * ratio as f32;
+    let src = Vector::<Point2f>::from_slice(&[Point2f::new(0.0, 0.0), Point2f::new(w, 0.0), Point2f::new(w, h), Point2f::new(0.0, h)]);
+    let dst = Vector::<Point2f>::from_slice(&[Point2f::new(inset, 0.0), Point2f::new(w - inset, 0.0), Point2f::new(w, h), Point2f::new(0.0, h)]);
+    let matrix = imgproc::get_perspective_transform(&src, &dst, 0)?;
+    let mut warped = Mat::default();
+    imgproc::warp_perspective(image, &mut warped, &matrix, image.size()?, imgproc::INTER_LINEAR, BORDER_CONSTANT, Scalar::all(WHITE))?;
+    Ok(warped)
+}
+
+fn blur(image: &Mat, sigma: f64) -> opencv::Result<Mat> {
+    let mut blurred = Mat::default();
+    imgproc::gaussian_blur(image, &mut blurred, Size::new(0, 0), sigma, sigma, BORDER_CONSTANT)?;
+    Ok(blurred)
+}
+
+fn noise(image: &Mat, stddev: f64, rng: &mut Rng) -> opencv::Result<Mat> {
+    let mut noisy = image.try_clone()?;
+    for pixel in noisy.data_bytes_mut()? {
+        *pixel = (*pixel as f64 + rng.next_gaussian() * stddev).round().clamp(0.0, 255.0) as u8;
+    }
+    Ok(noisy)
+}
+
+fn jpeg(image: &Mat, quality: i32) -> opencv::Result<Mat> {
+    let mut buffer = Vector::<u8>::new();
+    imgcodecs::imencode(".jpg", image, &mut buffer, &Vector::from_slice(&[imgcodecs::IMWRITE_JPEG_QUALITY, quality.clamp(1, 100)]))?;
+    imgcodecs::imdecode(&buffer, imgcodecs::IMREAD_GRAYSCALE)
+}
+
+fn contrast(image: &Mat, ratio: f64) -> opencv::Result<Mat> {
+    let mut faded = Mat::default();
+    image.convert_to(&mut faded, -1, ratio, 127.5 * (1.0 - ratio))?;
+    Ok(faded)
+}
+
+fn occlusion(image: &Mat, ratio: f64) -> opencv::Result<Mat> {
+    let mut covered = image.try_clone()?;
+    let width = (image.cols() as f64 * ratio).round() as i32;
+    let rect = Rect::new((image.cols() - width) / 2, 0, width, image.rows());
+    imgproc::rectangle(&mut covered, rect, Scalar::all(WHITE), imgproc::FILLED, imgproc::LINE_8, 0)?;
+    Ok(covered)
+}
+
+pub fn distort(image: &Mat, distortion: Distortion, rng: &mut Rng) -> Result<Mat, Exception> {
+    match distortion {
+        Distortion::None => image.try_clone(),
+        Distortion::Rotation(degrees) => rotate(image, degrees),
+        Distortion::Perspective(ratio) => perspective(image, ratio),
+        Distortion::Blur(sigma) => blur(image, sigma),
+        Distortion::Noise(stddev) => noise(image, stddev, rng),
+        Distortion::Jpeg(quality) => jpeg(image, quality),
+        Distortion::Contrast(ratio) => contrast(image, ratio),
+        Distortion::Occlusion(ratio) => occlusion(image, ratio),
+    }
+    .map_err(cv)
+}
+
+pub struct Sample {
+    pub symbology: Symbology,
+    // 期望的识别结果，含自动补上的校验位
+    pub text: String,
+    pub image: Mat,
+}
+
+// 每种码制生成 per_symbology 张无失真的图片
+pub fn generate_corpus(symbologies: &[Symbology], per_symbology: usize, seed: u64) -> Result<Vec<Sample>, Exception> {
+    let mut rng = Rng::new(seed);
+    let mut samples = Vec::new();
+    for &symbology in symbologies {
+        let options = RenderOptions {
+            module_size: if symbology == Symbology::QrCode { 4 } else { 3 },
+            bar_height: 120,
+            ..RenderOptions::default()
+        };
+        for _ in 0..per_symbology {
+            let encoded = encode(symbology, &random_data(symbology, &mut rng), &options)?;
+            let image = render_mat(&encoded, &options)?;
+            samples.push(Sample {
+                symbology,
+                text: encoded.text,
+                image,
+            });
+        }
+    }
+    Ok(samples)
+}
+
+// UPC-A 可能被识别为首位补 0 的 EAN-13
+fn is_match(sample: &Sample, code: &CodeInfo) -> bool {
+    code.code == sample.text || (sample.symbology == Symbology::UpcA && code.code == format!("0{}", sample.text))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RateReport {
+    pub symbology: &'static str,
+    pub distortion: &'static str,
+    pub level: f64,
+    pub total: usize,
+    // 识别出期望内容的样本数
+    pub decoded: usize,
+    // 有识别结果但都与期望内容不符的样本数
+    pub misread: usize,
+    pub decode_rate: f64,
+    pub misread_rate: f64,
+}
+
+// 对每个样本依次施加各档失真并识别，按 (码制, 失真, 档位) 汇总
+pub fn evaluate(samples: &[Sample], distortions: &[Distortion], options: &DecodeOptions, seed: u64) -> Result<Vec<RateReport>, Exception> {
+    let mut rng = Rng::new(seed);
+    let mut reports = Vec::new();
+    let mut symbologies = Vec::<Symbology>::new();
+    for sample in samples {
+        if !symbologies.contains(&sample.symbology) {
+            symbologies.push(sample.symbology);
+        }
+    }
+    for symbology in symbologies {
+        for &distortion in distortions {
+            let (mut total, mut decoded, mut misread) = (0, 0, 0);
+            for sample in samples.iter().filter(|s| s.symbology == symbology) {
+                let image = distort(&sample.image, distortion, &mut rng)?;
+                // 识别失败（如预处理报错）按未识别统计，不中断整个评测
+                let codes = detect_and_decode_with_options(&image, options).map(|r| r.codes).unwrap_or_default();
+                total += 1;
+                if codes.iter().any(|c| is_match(sample, c)) {
+                    decoded += 1;
+                } else if !codes.is_empty() {
+                    misread += 1;
+                }
+            }
+            let rate = |n: usize| if total == 0 { 0.0 } else { n as f64 / total as f64 };
+            reports.push(RateReport {
+                symbology: symbology.category(),
+                distortion: distortion.name(),
+                level: distortion.level(),
+                total,
+                decoded,
+                misread,
+                decode_rate: rate(decoded),
+                misread_rate: rate(misread),
+            });
+        }
+    }
+    Ok(reports)
+}
+
+pub fn format_reports(reports: &[RateReport]) -> String {
+    let mut table = format!("{:<10} {:<12} {:>8} {:>6} {:>8} {:>8}\n", "symbology", "distortion", "level", "total", "decode", "misread");
+    for r in reports {
+        table.push_str(&format!(
+            "{:<10} {:<12} {:>8} {:>6} {:>7.1}% {:>7.1}%\n",
+            r.symbology,
+            r.distortion,
+            r.level,
+            r.total,
+            r.decode_rate * 100.0,
+            r.misread_rate * 100.0
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rng_is_reproducible() {
+        let (mut a, mut b) = (Rng::new(7), Rng::new(7));
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert_ne!(Rng::new(0).next_u64(), 0);
+    }
+
+    #[test]
+    fn random_data_is_encodable() {
+        let mut rng = Rng::new(42);
+        for symbology in Symbology::ALL {
+            for _ in 0..50 {
+                let data = random_data(symbology, &mut rng);
+                assert!(encode(symbology, &data, &RenderOptions::default()).is_ok(), "{:?} {:?}", symbology, data);
+            }
+        }
+    }
+
+    #[test]
+    fn standard_levels_cover_every_distortion() {
+        let levels = Distortion::standard_levels();
+        for name in ["none", "rotation", "perspective", "blur", "noise", "jpeg", "contrast", "occlusion"] {
+            assert!(levels.iter().any(|d| d.name() == name), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use crate::service::dto::ProductInfo;
+use crate::service::geometry::{run_lengths, sample_modules};
+
+// EAN/UPC 编码表：每个数字 7 个模块，true 表示暗条
+const L_CODES: [u8; 10] = [0x0D, 0x19, 0x13, 0x3D, 0x23, 0x31, 0x2F, 0x3B, 0x37, 0x0B];
+const G_CODES: [u8; 10] = [0x27, 0x33, 0x1B, 0x21, 0x1D, 0x39, 0x05, 0x11, 0x09, 0x17];
+
+// EAN-13 首位数字决定左半部分 6 个数字的奇偶性（bit 为 1 表示使用 G 码）
+const EAN13_PARITY: [u8; 10] = [0x00, 0x0B, 0x0D, 0x0E, 0x13, 0x19, 0x1C, 0x15, 0x16, 0x1A];
+// UPC-E（数字系统 0）由校验位决定 6 个数字的奇偶性（bit 为 1 表示使用 G 码，即偶校验）
+const UPCE_PARITY: [u8; 10] = [0x38, 0x34, 0x32, 0x31, 0x2C, 0x26, 0x23, 0x2A, 0x29, 0x25];
+
+fn push_bits(modules: &mut Vec<bool>, bits: u8, count: u32) {
+    for i in (0..count).rev() {
+        modules.push((bits >> i) & 1 == 1);
+    }
+}
+
+fn push_digit(modules: &mut Vec<bool>, digit: u8, parity: char) {
+    let code = match parity {
+        'L' => L_CODES[digit as usize],
+        'G' => G_CODES[digit as usize],
+        // R 码是 L 码取反
+        _ => !L_CODES[digit as usize] & 0x7F,
+    };
+    push_bits(modules, code, 7);
+}
+
+pub(crate) fn parse_digits(text: &str) -> Option<Vec<u8>> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(text.bytes().map(|b| b - b'0').collect())
+}
+
+// GS1 模 10 校验位：从右往左（不含校验位）权重依次为 3、1
+pub(crate) fn gs1_check_digit(digits: &[u8]) -> u8 {
+    let sum = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| d as u32 * if i % 2 == 0 { 3 } else { 1 })
+        .sum::<u32>();
+    ((10 - sum % 10) % 10) as u8
+}
+
+pub(crate) fn ean13_modules(digits: &[u8]) -> Option<Vec<bool>> {
+    if digits.len() != 13 {
+        return None;
+    }
+    let parity = EAN13_PARITY[digits[0] as usize];
+    let mut modules = Vec::with_capacity(95);
+    push_bits(&mut modules, 0b101, 3);
+    for (i, &d) in digits[1..7].iter().enumerate() {
+        let g = (parity >> (5 - i)) & 1 == 1;
+        push_digit(&mut modules, d, if g { 'G' } else { 'L' });
+    }
+    push_bits(&mut modules, 0b01010, 5);
+    for &d in &digits[7..13] {
+        push_digit(&mut modules, d, 'R');
+    }
+    push_bits(&mut modules, 0b101, 3);
+    Some(modules)
+}
+
+pub(crate) fn ean8_modules(digits: &[u8]) -> Option<Vec<bool>> {
+    if digits.len() != 8 {
+        return None;
+    }
+    let mut modules = Vec::with_capacity(67);
+    push_bits(&mut modules, 0b101, 3);
+    for &d in &digits[0..4] {
+        push_digit(&mut modules, d, 'L');
+    }
+    push_bits(&mut modules, 0b01010, 5);
+    for &d in &digits[4..8] {
+        push_digit(&mut modules, d, 'R');
+    }
+    push_bits(&mut modules, 0b101, 3);
+    Some(modules)
+}
+
+// digits 为 8 位：数字系统 + 6 位数据 + 校验位
+pub(crate) fn upce_modules(digits: &[u8]) -> Option<Vec<bool>> {
+    if digits.len() != 8 || digits[0] > 1 {
+        return None;
+    }
+    let mut parity = UPCE_PARITY[digits[7] as usize];
+    if digits[0] == 1 {
+        parity = !parity & 0x3F;
+    }
+    let mut modules = Vec::with_capacity(51);
+    push_bits(&mut modules, 0b101, 3);
+    for (i, &d) in digits[1..7].iter().enumerate() {
+        let g = (parity >> (5 - i)) & 1 == 1;
+        push_digit(&mut modules, d, if g { 'G' } else { 'L' });
+    }
+    push_bits(&mut modules, 0b010101, 6);
+    Some(modules)
+}
+
+// 根据解码文本列出可能的码制及其模块序列，类型名与 OpenCV BarcodeDetector 返回的一致
+pub(crate) fn candidate_patterns(text: &str) -> Vec<(&'static str, Vec<bool>)> {
+    let mut candidates = Vec::new();
+    let digits = match parse_digits(text) {
+        Some(d) => d,
+        None => return candidates,
+    };
+    match digits.len() {
+        13 => candidates.extend(ean13_modules(&digits).map(|m| ("EAN_13", m))),
+        12 => {
+            let mut padded = vec![0u8];
+            padded.extend_from_slice(&digits);
+            candidates.extend(ean13_modules(&padded).map(|m| ("UPC_A", m)));
+        }
+        8 => {
+            candidates.extend(ean8_modules(&digits).map(|m| ("EAN_8", m)));
+            candidates.extend(upce_modules(&digits).map(|m| ("UPC_E", m)));
+        }
+        _ => {}
+    }
+    candidates
+}
+
+// EAN-5 附加码由校验值决定 5 个数字的奇偶性（bit 为 1 表示使用 G 码）
+const EAN5_PARITY: [u8; 10] = [0x18, 0x14, 0x12, 0x11, 0x0C, 0x06, 0x03, 0x0A, 0x09, 0x05];
+
+// GS1 厂商识别代码前缀（GTIN-13 前 3 位）对应的编码组织所在国家或地区及保留用途
+const GS1_PREFIXES: &[(u16, u16, &str)] = &[
+    (0, 19, "United States and Canada"),
+    (20, 29, "Restricted distribution"),
+    (30, 39, "United States"),
+    (40, 49, "Restricted distribution"),
+    (50, 59, "Coupons"),
+    (60, 139, "United States and Canada"),
+    (200, 299, "Restricted distribution"),
+    (300, 379, "France and Monaco"),
+    (380, 380, "Bulgaria"),
+    (383, 383, "Slovenia"),
+    (385, 385, "Croatia"),
+    (387, 387, "Bosnia and Herzegovina"),
+    (389, 389, "Montenegro"),
+    (390, 390, "Kosovo"),
+    (400, 440, "Germany"),
+    (450, 459, "Japan"),
+    (460, 469, "Russia"),
+    (470, 470, "Kyrgyzstan"),
+    (471, 471, "Taiwan"),
+    (474, 474, "Estonia"),
+    (475, 475, "Latvia"),
+    (476, 476, "Azerbaijan"),
+    (477, 477, "Lithuania"),
+    (478, 478, "Uzbekistan"),
+    (479, 479, "Sri Lanka"),
+    (480, 480, "Philippines"),
+    (481, 481, "Belarus"),
+    (482, 482, "Ukraine"),
+    (483, 483, "Turkmenistan"),
+    (484, 484, "Moldova"),
+    (485, 485, "Armenia"),
+    (486, 486, "Georgia"),
+    (487, 487, "Kazakhstan"),
+    (488, 488, "Tajikistan"),
+    (489, 489, "Hong Kong"),
+    (490, 499, "Japan"),
+    (500, 509, "United Kingdom"),
+    (520, 521, "Greece"),
+    (528, 528, "Lebanon"),
+    (529, 529, "Cyprus"),
+    (530, 530, "Albania"),
+    (531, 531, "North Macedonia"),
+    (535, 535, "Malta"),
+    (539, 539, "Ireland"),
+    (540, 549, "Belgium and Luxembourg"),
+    (560, 560, "Portugal"),
+    (569, 569, "Iceland"),
+    (570, 579, "Denmark"),
+    (590, 590, "Poland"),
+    (594, 594, "Romania"),
+    (599, 599, "Hungary"),
+    (600, 601, "South Africa"),
+    (603, 603, "Ghana"),
+    (604, 604, "Senegal"),
+    (608, 608, "Bahrain"),
+    (609, 609, "Mauritius"),
+    (611, 611, "Morocco"),
+    (613, 613, "Algeria"),
+    (615, 615, "Nigeria"),
+    (616, 616, "Kenya"),
+    (618, 618, "Côte d'Ivoire"),
+    (619, 619, "Tunisia"),
+    (620, 620, "Tanzania"),
+    (621, 621, "Syria"),
+    (622, 622, "Egypt"),
+    (623, 623, "Brunei"),
+    (624, 624, "Libya"),
+    (625, 625, "Jordan"),
+    (626, 626, "Iran"),
+    (627, 627, "Kuwait"),
+    (628, 628, "Saudi Arabia"),
+    (629, 629, "United Arab Emirates"),
+    (640, 649, "Finland"),
+    (690, 699, "China"),
+    (700, 709, "Norway"),
+    (729, 729, "Israel"),
+    (730, 739, "Sweden"),
+    (740, 740, "Guatemala"),
+    (741, 741, "El Salvador"),
+    (742, 742, "Honduras"),
+    (743, 743, "Nicaragua"),
+    (744, 744, "Costa Rica"),
+    (745, 745, "Panama"),
+    (746, 746, "Dominican Republic"),
+    (750, 750, "Mexico"),
+    (754, 755, "Canada"),
+    (759, 759, "Venezuela"),
+    (760, 769, "Switzerland and Liechtenstein"),
+    (770, 771, "Colombia"),
+    (773, 773, "Uruguay"),
+    (775, 775, "Peru"),
+    (777, 777, "Bolivia"),
+    (778, 779, "Argentina"),
+    (780, 780, "Chile"),
+    (784, 784, "Paraguay"),
+    (786, 786, "Ecuador"),
+    (789, 790, "Brazil"),
+    (800, 839, "Italy, San Marino and Vatican City"),
+    (840, 849, "Spain and Andorra"),
+    (850, 850, "Cuba"),
+    (858, 858, "Slovakia"),
+    (859, 859, "Czech Republic"),
+    (860, 860, "Serbia"),
+    (865, 865, "Mongolia"),
+    (867, 867, "North Korea"),
+    (868, 869, "Turkey"),
+    (870, 879, "Netherlands"),
+    (880, 880, "South Korea"),
+    (883, 883, "Myanmar"),
+    (884, 884, "Cambodia"),
+    (885, 885, "Thailand"),
+    (888, 888, "Singapore"),
+    (890, 890, "India"),
+    (893, 893, "Vietnam"),
+    (896, 896, "Pakistan"),
+    (899, 899, "Indonesia"),
+    (900, 919, "Austria"),
+    (930, 939, "Australia"),
+    (940, 949, "New Zealand"),
+    (950, 950, "GS1 Global Office"),
+    (951, 951, "GS1 Global Office (EPCglobal)"),
+    (955, 955, "Malaysia"),
+    (958, 958, "Macau"),
+    (960, 969, "GS1 Global Office (GTIN-8)"),
+    (977, 977, "Serial publications (ISSN)"),
+    (978, 979, "Bookland (ISBN)"),
+    (980, 980, "Refund receipts"),
+    (981, 984, "Common currency coupons"),
+    (990, 999, "Coupons"),
+];
+
+// UPC-E 展开为 UPC-A：digits 为 8 位（数字系统 + 6 位数据 + 校验位），由第 6 位数据决定补零的位置
+pub(crate) fn expand_upce(digits: &[u8]) -> Option<Vec<u8>> {
+    if digits.len() != 8 || digits[0] > 1 {
+        return None;
+    }
+    let (s, d, check) = (digits[0], &digits[1..7], digits[7]);
+    let mut expanded = vec![s];
+    match d[5] {
+        0..=2 => {
+            expanded.extend_from_slice(&[d[0], d[1], d[5], 0, 0, 0, 0]);
+            expanded.extend_from_slice(&d[2..5]);
+        }
+        3 => {
+            expanded.extend_from_slice(&d[0..3]);
+            expanded.extend_from_slice(&[0, 0, 0, 0, 0]);
+            expanded.extend_from_slice(&d[3..5]);
+        }
+        4 => {
+            expanded.extend_from_slice(&d[0..4]);
+            expanded.extend_from_slice(&[0, 0, 0, 0, 0]);
+            expanded.push(d[4]);
+        }
+        _ => {
+            expanded.extend_from_slice(&d[0..5]);
+            expanded.extend_from_slice(&[0, 0, 0, 0, d[5]]);
+        }
+    }
+    expanded.push(check);
+    Some(expanded)
+}
+
+fn to_text(digits: &[u8]) -> String {
+    digits.iter().map(|d| (b'0' + d) as char).collect()
+}
+
+// ISBN-10 / ISSN 的模 11 校验位，权重从 digits.len() + 1 递减到 2，余 10 记为 X
+fn mod11_check_digit(digits: &[u8]) -> char {
+    let weight = digits.len() as u32 + 1;
+    let sum = digits.iter().enumerate().map(|(i, &d)| d as u32 * (weight - i as u32)).sum::<u32>();
+    match (11 - sum % 11) % 11 {
+        10 => 'X',
+        c => (b'0' + c as u8) as char,
+    }
+}
+
+pub(crate) fn country_of_prefix(gtin13: &[u8]) -> Option<&'static str> {
+    if gtin13.len() < 3 {
+        return None;
+    }
+    let prefix = gtin13[0] as u16 * 100 + gtin13[1] as u16 * 10 + gtin13[2] as u16;
+    GS1_PREFIXES
+        .iter()
+        .find(|(start, end, _)| (*start..=*end).contains(&prefix))
+        .map(|(_, _, name)| *name)
+}
+
+// EAN-5 附加码用于图书建议零售价：首位为币种，后 4 位为以分计的价格；90000 表示无价格
+fn suggested_price(addon: &str) -> Option<String> {
+    let currency = match &addon[..1] {
+        "0" => "£",
+        "5" => "$",
+        "9" => {
+            return match addon {
+                "90000" => None,
+                "99991" => Some("0.00".to_string()),
+                "99990" => Some("Used".to_string()),
+                _ => None,
+            };
+        }
+        _ => "",
+    };
+    let cents = addon[1..].parse::<u32>().ok()?;
+    Some(format!("{}{}.{:02}", currency, cents / 100, cents % 100))
+}
+
+// 由码制与解码内容生成商品码附加信息，category 为 candidate_patterns 给出的类型名
+pub(crate) fn product_info(category: &str, text: &str, addon: Option<String>) -> Option<ProductInfo> {
+    let digits = parse_digits(text)?;
+    let mut info = ProductInfo::default();
+    let gtin13 = match (category, digits.len()) {
+        ("EAN_13", 13) => digits.clone(),
+        ("UPC_A", 12) => [&[0u8][..], &digits].concat(),
+        ("UPC_E", 8) => {
+            let upc_a = expand_upce(&digits)?;
+            info.upc_a = Some(to_text(&upc_a));
+            [&[0u8][..], &upc_a].concat()
+        }
+        ("EAN_8", 8) => {
+            // EAN-8 的前缀与 GTIN-13 共用同一张分配表，但 0 开头的为店内码（RCN-8）
+            info.gtin = format!("{:0>14}", text);
+            info.country = if digits[0] == 0 {
+                Some("Restricted distribution".to_string())
+            } else {
+                country_of_prefix(&digits).map(|c| c.to_string())
+            };
+            info.addon = addon;
+            return Some(info);
+        }
+        _ => return None,
+    };
+    info.gtin = format!("0{}", to_text(&gtin13));
+    info.country = country_of_prefix(&gtin13).map(|c| c.to_string());
+    match &gtin13[..3] {
+        [9, 7, 8] | [9, 7, 9] => {
+            info.isbn13 = Some(to_text(&gtin13));
+            // 979 前缀的 ISBN 没有对应的 ISBN-10
+            if gtin13[2] == 8 {
+                info.isbn10 = Some(format!("{}{}", to_text(&gtin13[3..12]), mod11_check_digit(&gtin13[3..12])));
+            }
+        }
+        [9, 7, 7] => {
+            info.issn = Some(format!("{}-{}{}", to_text(&gtin13[3..7]), to_text(&gtin13[7..10]), mod11_check_digit(&gtin13[3..10])));
+        }
+        _ => {}
+    }
+    match addon.as_deref().map(|a| a.len()) {
+        Some(2) => info.issue_number = addon.as_deref().and_then(|a| a.parse::<u8>().ok()),
+        Some(5) => info.suggested_price = addon.as_deref().and_then(suggested_price),
+        _ => {}
+    }
+    info.addon = addon;
+    Some(info)
+}
+
+fn bits_to_code(bits: &[bool]) -> u8 {
+    bits.iter().fold(0u8, |code, &b| (code << 1) | b as u8)
+}
+
+// 按附加码的模块序列解码：起始符 1011，数字间以 01 分隔，每个数字 7 个模块，使用 L 码或 G 码
+fn decode_addon_modules(modules: &[bool], count: usize) -> Option<String> {
+    if modules.len() != 4 + count * 7 + (count - 1) * 2 || bits_to_code(&modules[..4]) != 0b1011 {
+        return None;
+    }
+    let mut digits = Vec::with_capacity(count);
+    let mut parity = 0u8;
+    let mut pos = 4;
+    for k in 0..count {
+        if k > 0 {
+            if bits_to_code(&modules[pos..pos + 2]) != 0b01 {
+                return None;
+            }
+            pos += 2;
+        }
+        let code = bits_to_code(&modules[pos..pos + 7]);
+        let (digit, g) = match L_CODES.iter().position(|&c| c == code) {
+            Some(d) => (d as u8, false),
+            None => (G_CODES.iter().position(|&c| c == code)? as u8, true),
+        };
+        digits.push(digit);
+        parity = (parity << 1) | g as u8;
+        pos += 7;
+    }
+    // EAN-2 的奇偶性为数值除以 4 的余数，EAN-5 的奇偶性由加权和的个位决定
+    let expected = if count == 2 {
+        (digits[0] * 10 + digits[1]) % 4
+    } else {
+        let sum = digits.iter().enumerate().map(|(i, &d)| d as u32 * if i % 2 == 0 { 3 } else { 9 }).sum::<u32>();
+        EAN5_PARITY[(sum % 10) as usize]
+    };
+    if parity != expected {
+        return None;
+    }
+    Some(to_text(&digits))
+}
+
+// 在按阅读方向排列的一行像素中寻找主码右侧的 EAN-2/EAN-5 附加码。
+// 主码内部的空最宽 4 个模块，以不少于 5 个模块的空把扫描线切分为若干段，
+// 主码之后（第二段起）长度接近 20 或 47 个模块且起始符、分隔符、奇偶校验均吻合的段即为附加码
+pub(crate) fn decode_addon(row: &[u8], module_width: f32) -> Option<String> {
+    let quiet = (module_width * 5.0).max(1.0) as usize;
+    let mut segments = Vec::<(usize, usize)>::new();
+    let mut current: Option<(usize, usize)> = None;
+    let mut x = 0usize;
+    for (length, dark) in run_lengths(row) {
+        if dark {
+            current = Some(current.map_or((x, x + length), |(start, _)| (start, x + length)));
+        } else if length >= quiet {
+            segments.extend(current.take());
+        }
+        x += length;
+    }
+    segments.extend(current);
+
+    segments.iter().skip(1).find_map(|&(start, stop)| {
+        let modules = (stop - start) as f32 / module_width;
+        [(20usize, 2usize), (47, 5)]
+            .iter()
+            .filter(|(n, _)| (modules - *n as f32).abs() <= *n as f32 * 0.25)
+            .find_map(|&(n, count)| decode_addon_modules(&sample_modules(row, start, stop, n)?, count))
+    })
+}
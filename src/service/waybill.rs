@@ -0,0 +1,233 @@
+use std::fs;
+use std::sync::OnceLock;
+use regex::Regex;
+use serde::Deserialize;
+use crate::basic::Exception;
+use crate::service::dto::Waybill;
+
+// 运单号自带的校验方式
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WaybillChecksum {
+    // 万国邮联 S10 标准（EMS 国际及部分国内邮件）：2 字母 + 8 位序号 + 校验位 + 2 字母国家代码
+    UpuS10,
+}
+
+// 一条承运商识别规则，可从配置文件加载：
+// [{"carrier": "ZJS", "name": "宅急送", "pattern": "^ZJS\\d{12}$", "confidence": 0.9}]
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaybillRule {
+    pub carrier: String,
+    pub name: String,
+    // 匹配整个运单号的正则表达式
+    pub pattern: String,
+    // 规则单独命中时的置信度，0..1
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+    #[serde(default)]
+    pub checksum: Option<WaybillChecksum>,
+}
+
+fn default_confidence() -> f32 {
+    0.8
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    rule: WaybillRule,
+    regex: Regex,
+}
+
+// 内置规则：带字母前缀的单号基本可以唯一确定承运商，纯数字单号各家号段有重叠，置信度较低。
+// 纯数字规则至少要求固定的号段前缀，不设任意 12 位数字之类的兜底规则，以免把 GTIN-12 或数字内容误认为运单号
+const BUILTIN_RULES: [(&str, &str, &str, f32, Option<WaybillChecksum>); 12] = [
+    ("SF", "顺丰速运", r"^SF\d{13}$", 0.98, None),
+    ("JD", "京东物流", r"^JD[0-9A-Z]{11,16}$", 0.98, None),
+    ("YTO", "圆通速递", r"^YT\d{13}$", 0.98, None),
+    ("ZTO", "中通快递", r"^7[3-8]\d{10}$", 0.6, None),
+    ("ZTO", "中通快递", r"^7[3-8]\d{12}$", 0.6, None),
+    ("STO", "申通快递", r"^77[0-9]\d{12}$", 0.6, None),
+    ("YD", "韵达速递", r"^(31|43|46|50|53|56|60|61)\d{11}$", 0.6, None),
+    ("EMS", "中国邮政 EMS", r"^E[A-Z]\d{9}CN$", 0.95, Some(WaybillChecksum::UpuS10)),
+    ("EMS", "中国邮政 EMS", r"^1\d{12}$", 0.5, None),
+    ("CHINAPOST", "中国邮政", r"^[CKLR][A-Z]\d{9}CN$", 0.9, Some(WaybillChecksum::UpuS10)),
+    ("JT", "极兔速递", r"^JT\d{13}$", 0.98, None),
+    ("DBL", "德邦快递", r"^DPK\d{12}$", 0.98, None),
+];
+
+fn compile(rule: WaybillRule) -> Result<CompiledRule, Exception> {
+    let regex = Regex::new(&rule.pattern).map_err(|e| Exception::new(0, format!("Invalid waybill pattern {:?}: {}", rule.pattern, e)))?;
+    if !(0.0..=1.0).contains(&rule.confidence) {
+        return Err(Exception::new(0, format!("Waybill rule confidence must be within 0..1, got {}", rule.confidence)));
+    }
+    Ok(CompiledRule { rule, regex })
+}
+
+// 一组运单号识别规则，随 DecodeOptions 传入识别流程。各调用方持有自己的规则集，互不影响；
+// 默认只含内置规则，克隆时共享已编译的正则表达式
+#[derive(Debug, Clone)]
+pub struct WaybillRules {
+    rules: Vec<CompiledRule>,
+}
+
+impl Default for WaybillRules {
+    fn default() -> Self {
+        Self::builtin().clone()
+    }
+}
+
+impl WaybillRules {
+    // 内置规则只编译一次
+    pub fn builtin() -> &'static WaybillRules {
+        static BUILTIN: OnceLock<WaybillRules> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            let rules = BUILTIN_RULES
+                .iter()
+                .filter_map(|&(carrier, name, pattern, confidence, checksum)| {
+                    compile(WaybillRule {
+                        carrier: carrier.to_string(),
+                        name: name.to_string(),
+                        pattern: pattern.to_string(),
+                        confidence,
+                        checksum,
+                    })
+                    .ok()
+                })
+                .collect();
+            WaybillRules { rules }
+        })
+    }
+
+    // 不含内置规则的空规则集，只按自定义规则识别
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    // 追加自定义规则，与已有规则一起参与匹配；任何一条规则不合法时都不会追加
+    pub fn add(&mut self, rules: Vec<WaybillRule>) -> Result<usize, Exception> {
+        let compiled = rules.into_iter().map(compile).collect::<Result<Vec<CompiledRule>, Exception>>()?;
+        let count = compiled.len();
+        self.rules.extend(compiled);
+        Ok(count)
+    }
+
+    // 从 JSON 文件加载规则并追加，文件内容为 WaybillRule 数组
+    pub fn load(&mut self, path: &str) -> Result<usize, Exception> {
+        let text = fs::read_to_string(path).map_err(|e| Exception::new(0, format!("Failed to read waybill rules {}: {}", path, e)))?;
+        let rules = serde_json::from_str::<Vec<WaybillRule>>(&text).map_err(|e| Exception::new(0, format!("Failed to parse waybill rules {}: {}", path, e)))?;
+        self.add(rules)
+    }
+
+    // 同一承运商取最高置信度；多个承运商同时命中时，置信度按各自在命中总和中的占比折减，
+    // 并在 alternatives 中列出其余承运商
+    pub fn recognize(&self, text: &str) -> Option<Waybill> {
+        let number = text.trim();
+        if number.is_empty() {
+            return None;
+        }
+        let mut matches = Vec::<(&str, &str, f32)>::new();
+        for compiled in self.rules.iter().filter(|c| c.regex.is_match(number)) {
+            let rule = &compiled.rule;
+            if rule.checksum == Some(WaybillChecksum::UpuS10) && !upu_s10_valid(number) {
+                continue;
+            }
+            match matches.iter_mut().find(|m| m.0 == rule.carrier) {
+                Some(existing) => existing.2 = existing.2.max(rule.confidence),
+                None => matches.push((&rule.carrier, &rule.name, rule.confidence)),
+            }
+        }
+        matches.sort_by(|a, b| b.2.total_cmp(&a.2));
+        let total = matches.iter().map(|m| m.2).sum::<f32>();
+        let (carrier, name, confidence) = *matches.first()?;
+        Some(Waybill {
+            number: number.to_string(),
+            carrier: carrier.to_string(),
+            carrier_name: name.to_string(),
+            confidence: if total > 0.0 { confidence * confidence / total } else { 0.0 },
+            alternatives: matches[1..].iter().map(|m| m.0.to_string()).collect(),
+        })
+    }
+}
+
+// S10 校验位：8 位序号按 8,6,4,2,3,5,9,7 加权，11 减去模 11 的余数，10 记为 0，11 记为 5
+fn upu_s10_valid(number: &str) -> bool {
+    let digits = match number.get(2..11) {
+        Some(d) if d.bytes().all(|b| b.is_ascii_digit()) => d.bytes().map(|b| (b - b'0') as u32).collect::<Vec<u32>>(),
+        _ => return false,
+    };
+    let sum = digits[..8].iter().zip([8, 6, 4, 2, 3, 5, 9, 7]).map(|(d, w)| d * w).sum::<u32>();
+    let check = match 11 - sum % 11 {
+        10 => 0,
+        11 => 5,
+        c => c,
+    };
+    check == digits[8]
+}
+
+// 按内置规则识别运单号，需要自定义规则时使用 WaybillRules::recognize
+pub fn recognize_waybill(text: &str) -> Option<Waybill> {
+    WaybillRules::builtin().recognize(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_prefixed_and_numeric_ranges() {
+        let sf = recognize_waybill("SF1234567890123").unwrap();
+        assert_eq!(sf.carrier, "SF");
+        assert!((sf.confidence - 0.98).abs() < 1e-6);
+        let zto = recognize_waybill("75312345678901").unwrap();
+        assert_eq!(zto.carrier, "ZTO");
+        assert!(zto.alternatives.is_empty());
+        // S10 校验位：47312482 加权和 200，200 % 11 = 2，11 - 2 = 9
+        assert_eq!(recognize_waybill("EA473124829CN").unwrap().carrier, "EMS");
+        assert!(recognize_waybill("EA473124828CN").is_none());
+    }
+
+    #[test]
+    fn plain_twelve_digits_are_not_waybills() {
+        // GTIN-12 / UPC-A 及任意 12 位数字不再命中兜底规则
+        assert!(recognize_waybill("036000291452").is_none());
+        assert!(recognize_waybill("A12345678901").is_none());
+    }
+
+    fn rule(carrier: &str, pattern: &str, confidence: f32) -> WaybillRule {
+        WaybillRule {
+            carrier: carrier.to_string(),
+            name: carrier.to_string(),
+            pattern: pattern.to_string(),
+            confidence,
+            checksum: None,
+        }
+    }
+
+    #[test]
+    fn custom_rules_stay_in_their_own_set() {
+        let mut rules = WaybillRules::default();
+        assert_eq!(rules.add(vec![rule("ZJS", r"^ZJS\d{12}$", 0.9)]).unwrap(), 1);
+        assert_eq!(rules.recognize("ZJS123456789012").unwrap().carrier, "ZJS");
+        // 内置规则与其他规则集不受影响
+        assert!(recognize_waybill("ZJS123456789012").is_none());
+        assert!(WaybillRules::default().recognize("ZJS123456789012").is_none());
+        assert!(WaybillRules::empty().recognize("SF1234567890123").is_none());
+    }
+
+    #[test]
+    fn overlapping_carriers_share_confidence() {
+        let mut rules = WaybillRules::empty();
+        rules.add(vec![rule("A", r"^\d{10}$", 0.6), rule("B", r"^\d{10}$", 0.2), rule("A", r"^1\d{9}$", 0.8)]).unwrap();
+        let waybill = rules.recognize("1234567890").unwrap();
+        assert_eq!((waybill.carrier.as_str(), waybill.alternatives.clone()), ("A", vec!["B".to_string()]));
+        assert!((waybill.confidence - 0.8 * 0.8 / 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected_without_partial_registration() {
+        let mut rules = WaybillRules::empty();
+        assert!(rules.add(vec![rule("A", r"^A\d+$", 0.5), rule("B", r"^(", 0.5)]).is_err());
+        assert!(rules.add(vec![rule("C", r"^C\d+$", f32::NAN)]).is_err());
+        assert!(rules.recognize("A123").is_none());
+    }
+}
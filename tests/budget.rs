@@ -0,0 +1,63 @@
+use barcode_detector::service::barcode::detect_and_decode_with_options;
+use barcode_detector::service::encoder::Symbology;
+use barcode_detector::service::options::{CancellationToken, DecodeOptions};
+use barcode_detector::service::synthetic::generate_corpus;
+
+#[test]
+fn cancelled_before_start_returns_partial_result() {
+    let samples = generate_corpus(&[Symbology::Ean13], 1, 3).unwrap();
+    let token = CancellationToken::new();
+    let options = DecodeOptions {
+        cancellation: Some(token.clone()),
+        ..DecodeOptions::default()
+    };
+    token.cancel();
+    let result = detect_and_decode_with_options(&samples[0].image, &options).unwrap();
+    assert!(result.timed_out);
+    assert!(result.codes.is_empty());
+}
+
+// 预算在创建检测器、detect_multi 之前即已用尽，不做任何识别
+#[test]
+fn zero_timeout_times_out() {
+    for symbology in [Symbology::Ean13, Symbology::QrCode] {
+        let samples = generate_corpus(&[symbology], 1, 3).unwrap();
+        let options = DecodeOptions {
+            timeout_ms: Some(0),
+            profile: true,
+            ..DecodeOptions::default()
+        };
+        let result = detect_and_decode_with_options(&samples[0].image, &options).unwrap();
+        assert!(result.timed_out);
+        assert!(result.codes.is_empty());
+        let timings = result.timings.unwrap();
+        assert_eq!((timings.setup, timings.detect, timings.enhance, timings.decode), (0.0, 0.0, 0.0, 0.0));
+    }
+}
+
+#[test]
+fn generous_timeout_completes() {
+    let samples = generate_corpus(&[Symbology::Ean13], 1, 3).unwrap();
+    let options = DecodeOptions {
+        timeout_ms: Some(60_000),
+        ..DecodeOptions::default()
+    };
+    let result = detect_and_decode_with_options(&samples[0].image, &options).unwrap();
+    assert!(!result.timed_out);
+    assert_eq!(result.codes.len(), 1);
+}
+
+// 未触发的取消标记不影响识别，全部工作完成后不会被记为超时
+#[test]
+fn untriggered_cancellation_completes() {
+    let samples = generate_corpus(&[Symbology::QrCode], 1, 3).unwrap();
+    let options = DecodeOptions {
+        cancellation: Some(CancellationToken::new()),
+        timeout_ms: Some(60_000),
+        ..DecodeOptions::default()
+    };
+    let result = detect_and_decode_with_options(&samples[0].image, &options).unwrap();
+    assert!(!result.timed_out);
+    assert_eq!(result.codes.len(), 1);
+    assert_eq!(result.codes[0].category, "QR_CODE");
+}
@@ -0,0 +1,104 @@
+use barcode_detector::service::barcode::detect_and_decode_with_options;
+use barcode_detector::service::encoder::{encode, render_mat, RenderOptions, Symbology};
+use barcode_detector::service::options::DecodeOptions;
+use opencv::core::{flip, Mat};
+use barcode_detector::service::synthetic::{evaluate, format_reports, generate_corpus, Distortion};
+
+// 要求稳定识别的码制；Code 39、ITF 同样由 rxing 在检出区域内解码，只在完整报告中统计
+const DECODABLE: [Symbology; 4] = [Symbology::Ean13, Symbology::UpcA, Symbology::Code128, Symbology::QrCode];
+
+#[test]
+fn clean_images_decode() {
+    let samples = generate_corpus(&DECODABLE, 5, 1).unwrap();
+    let reports = evaluate(&samples, &[Distortion::None], &DecodeOptions::default(), 1).unwrap();
+    for report in &reports {
+        assert_eq!(report.decoded, report.total, "{:?}", report);
+        assert_eq!(report.misread, 0, "{:?}", report);
+    }
+}
+
+#[test]
+fn mild_distortions_decode() {
+    let samples = generate_corpus(&DECODABLE, 5, 2).unwrap();
+    let distortions = [Distortion::Rotation(5.0), Distortion::Blur(0.5), Distortion::Noise(5.0), Distortion::Jpeg(90), Distortion::Contrast(0.5)];
+    let reports = evaluate(&samples, &distortions, &DecodeOptions::default(), 2).unwrap();
+    for report in &reports {
+        assert!(report.decode_rate >= 0.8, "{:?}", report);
+        assert_eq!(report.misread, 0, "{:?}", report);
+    }
+}
+
+// 每个一维码都应给出码制：EAN/UPC 来自条空比对或解码文本，Code 128 来自 rxing；一维码不报告镜像
+#[test]
+fn linear_codes_report_category() {
+    let samples = generate_corpus(&[Symbology::Ean13, Symbology::UpcA, Symbology::Code128], 3, 4).unwrap();
+    for sample in &samples {
+        let result = detect_and_decode_with_options(&sample.image, &DecodeOptions::default()).unwrap();
+        let code = &result.codes[0];
+        // UPC-A 可能被识别为首位补 0 的 EAN-13
+        let expected = if sample.symbology == Symbology::UpcA && code.code.len() == 13 { "EAN_13" } else { sample.symbology.category() };
+        assert_eq!(code.category, expected, "{:?}", sample.text);
+        assert!(!code.orientation.mirrored);
+    }
+}
+
+// 快递面单上的运单号为 Code 128，解码后应识别出承运商；每种码制都应能解回生成时的内容
+#[test]
+fn code128_waybill_numbers_decode() {
+    let options = RenderOptions {
+        module_size: 3,
+        bar_height: 120,
+        ..RenderOptions::default()
+    };
+    for (number, carrier) in [("SF1234567890123", "SF"), ("75312345678901", "ZTO")] {
+        let encoded = encode(Symbology::Code128, number, &options).unwrap();
+        let image = render_mat(&encoded, &options).unwrap();
+        let result = detect_and_decode_with_options(&image, &DecodeOptions::default()).unwrap();
+        let code = result.codes.iter().find(|c| c.code == number).unwrap_or_else(|| panic!("{} not decoded: {:?}", number, result.codes));
+        assert_eq!(code.category, "CODE_128");
+        assert_eq!(code.waybill.as_ref().map(|w| w.carrier.as_str()), Some(carrier));
+    }
+}
+
+#[test]
+fn code39_and_itf_round_trip() {
+    let options = RenderOptions {
+        module_size: 3,
+        bar_height: 120,
+        ..RenderOptions::default()
+    };
+    for (symbology, text) in [(Symbology::Code39, "PART-42"), (Symbology::Itf, "12345678")] {
+        let encoded = encode(symbology, text, &options).unwrap();
+        let image = render_mat(&encoded, &options).unwrap();
+        let result = detect_and_decode_with_options(&image, &DecodeOptions::default()).unwrap();
+        let code = result.codes.iter().find(|c| c.code == text).unwrap_or_else(|| panic!("{} not decoded: {:?}", text, result.codes));
+        assert_eq!(code.category, symbology.category());
+    }
+}
+
+// 从背面或经反射看到的 QR 码，rxing 按镜像解出并报告 mirrored
+#[test]
+fn mirrored_qr_is_reported() {
+    let options = RenderOptions {
+        module_size: 4,
+        ..RenderOptions::default()
+    };
+    let encoded = encode(Symbology::QrCode, "https://example.com/mirrored", &options).unwrap();
+    let image = render_mat(&encoded, &options).unwrap();
+    let mut flipped = Mat::default();
+    flip(&image, &mut flipped, 1).unwrap();
+    for (image, mirrored) in [(image, false), (flipped, true)] {
+        let result = detect_and_decode_with_options(&image, &DecodeOptions::default()).unwrap();
+        assert_eq!(result.codes[0].code, "https://example.com/mirrored");
+        assert_eq!(result.codes[0].orientation.mirrored, mirrored);
+    }
+}
+
+// 完整报告耗时较长：cargo test --test decode_rate -- --ignored --nocapture
+#[test]
+#[ignore]
+fn decode_rate_report() {
+    let samples = generate_corpus(&Symbology::ALL, 20, 42).unwrap();
+    let reports = evaluate(&samples, &Distortion::standard_levels(), &DecodeOptions::default(), 42).unwrap();
+    println!("{}", format_reports(&reports));
+}
@@ -0,0 +1,196 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use barcode_detector::service::barcode::detect_and_decode_with_options;
+use barcode_detector::service::image::read_gray_mat_from_path;
+use barcode_detector::service::options::DecodeOptions;
+
+// 回归测试：tests/golden 下每张图片（png/jpg/jpeg/bmp）走完整流程识别，结果与同名 .json 比对。
+// 只比较期望结果中出现的字段，手写的期望可以只列出关心的字段；数组长度必须一致。
+// 行为有意变化后重新生成期望结果：BLESS=1 cargo test --test golden
+
+// 坐标类字段允许的误差：像素坐标、归一化坐标、角度（度）。
+// 期望结果顶层的 "tolerance" 可以放宽误差，用于按生成参数推算、未经 bless 的几何期望
+#[derive(Clone, Copy)]
+struct Tolerance {
+    pixels: f64,
+    normalized: f64,
+    angle: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            pixels: 2.0,
+            normalized: 0.01,
+            angle: 1.0,
+        }
+    }
+}
+
+impl Tolerance {
+    // 取出并移除期望结果中的 "tolerance"，未列出的项使用默认值
+    fn take_from(expected: &mut Value) -> Self {
+        let mut tolerance = Self::default();
+        if let Some(Value::Object(overrides)) = expected.as_object_mut().and_then(|e| e.remove("tolerance")) {
+            let get = |key: &str, default: f64| overrides.get(key).and_then(Value::as_f64).unwrap_or(default);
+            tolerance = Self {
+                pixels: get("pixels", tolerance.pixels),
+                normalized: get("normalized", tolerance.normalized),
+                angle: get("angle", tolerance.angle),
+            };
+        }
+        tolerance
+    }
+
+    fn for_path(&self, path: &str) -> Option<f64> {
+        if path.contains("normalized") {
+            Some(self.normalized)
+        } else if path.ends_with("angle") {
+            Some(self.angle)
+        } else if path.contains("points") || path.contains("geometry") {
+            Some(self.pixels)
+        } else {
+            None
+        }
+    }
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn is_image(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref(),
+        Some("png" | "jpg" | "jpeg" | "bmp")
+    )
+}
+
+// 同一张图片中多个条码的输出顺序不保证稳定，按码制与内容排序后再比较
+fn normalize(mut result: Value) -> Value {
+    for key in ["codes", "messages"] {
+        if let Some(Value::Array(items)) = result.get_mut(key) {
+            items.sort_by_key(|item| format!("{}|{}", item["category"], item["code"]));
+        }
+    }
+    result
+}
+
+fn diff(path: &str, expected: &Value, actual: &Value, tolerance: Tolerance, errors: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(e), Value::Object(a)) => {
+            for (key, e) in e {
+                diff(&format!("{}.{}", path, key), e, a.get(key).unwrap_or(&Value::Null), tolerance, errors);
+            }
+        }
+        (Value::Array(e), Value::Array(a)) => {
+            if e.len() != a.len() {
+                errors.push(format!("{}: expected {} items, got {}", path, e.len(), a.len()));
+                return;
+            }
+            for (i, (e, a)) in e.iter().zip(a).enumerate() {
+                diff(&format!("{}[{}]", path, i), e, a, tolerance, errors);
+            }
+        }
+        (Value::Number(e), Value::Number(a)) => {
+            let (e, a) = (e.as_f64().unwrap_or(f64::NAN), a.as_f64().unwrap_or(f64::NAN));
+            let mut delta = (e - a).abs();
+            if path.ends_with("angle") {
+                // -179.9 与 180 是同一方向
+                delta = delta.min(360.0 - delta);
+            }
+            if delta > tolerance.for_path(path).unwrap_or(0.0) {
+                errors.push(format!("{}: expected {}, got {}", path, e, a));
+            }
+        }
+        (e, a) if e != a => errors.push(format!("{}: expected {}, got {}", path, e, a)),
+        _ => {}
+    }
+}
+
+#[test]
+fn golden_images() {
+    let bless = std::env::var_os("BLESS").is_some();
+    let mut images = match fs::read_dir(golden_dir()) {
+        Ok(entries) => entries.filter_map(|e| e.ok().map(|e| e.path())).filter(|p| is_image(p)).collect::<Vec<PathBuf>>(),
+        Err(_) => Vec::new(),
+    };
+    images.sort();
+
+    let mut failures = Vec::new();
+    for image in &images {
+        let name = image.file_name().unwrap().to_string_lossy().to_string();
+        let gray = read_gray_mat_from_path(image.to_str().unwrap()).unwrap_or_else(|e| panic!("Failed to read {}: {:?}", name, e));
+        let result = detect_and_decode_with_options(&gray, &DecodeOptions::default()).unwrap_or_else(|e| panic!("Failed to decode {}: {:?}", name, e));
+        let actual = normalize(serde_json::to_value(&result).unwrap());
+        let golden = image.with_extension("json");
+
+        if bless {
+            fs::write(&golden, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap();
+            println!("Blessed {}", golden.display());
+            continue;
+        }
+        let mut expected = match fs::read_to_string(&golden) {
+            Ok(text) => normalize(serde_json::from_str::<Value>(&text).unwrap_or_else(|e| panic!("Invalid golden {}: {}", golden.display(), e))),
+            Err(_) => {
+                failures.push(format!("{}: missing golden {}, run with BLESS=1 to create it", name, golden.display()));
+                continue;
+            }
+        };
+        let tolerance = Tolerance::take_from(&mut expected);
+        let mut errors = Vec::new();
+        diff("$", &expected, &actual, tolerance, &mut errors);
+        failures.extend(errors.into_iter().map(|e| format!("{}: {}", name, e)));
+    }
+    assert!(failures.is_empty(), "{} golden mismatches:\n{}", failures.len(), failures.join("\n"));
+}
+
+fn diff_errors(mut expected: Value, actual: Value) -> Vec<String> {
+    let tolerance = Tolerance::take_from(&mut expected);
+    let mut errors = Vec::new();
+    diff("$", &expected, &actual, tolerance, &mut errors);
+    errors
+}
+
+#[test]
+fn diff_applies_tolerances() {
+    let code = |x: f64, angle: f64, normalized: f64| {
+        serde_json::json!({
+            "code": "4006381333931",
+            "points": [{"x": x, "y": 10.0}],
+            "geometry": {"angle": angle, "normalized_points": [{"x": normalized, "y": 0.5}]}
+        })
+    };
+    let expected = code(100.0, 179.5, 0.25);
+    assert!(diff_errors(expected.clone(), code(101.5, -179.8, 0.255)).is_empty());
+
+    let errors = diff_errors(expected.clone(), code(103.0, 179.5, 0.25));
+    assert_eq!(errors, vec!["$.points[0].x: expected 100, got 103".to_string()]);
+    assert_eq!(diff_errors(expected.clone(), code(100.0, 177.0, 0.25)).len(), 1);
+    assert_eq!(diff_errors(expected, code(100.0, 179.5, 0.27)).len(), 1);
+}
+
+#[test]
+fn diff_compares_only_expected_fields() {
+    let expected = serde_json::json!({"codes": [{"code": "SF1234567890123"}]});
+    let actual = serde_json::json!({"codes": [{"code": "SF1234567890123", "category": "CODE_128"}], "timed_out": false});
+    assert!(diff_errors(expected.clone(), actual).is_empty());
+
+    assert_eq!(diff_errors(expected.clone(), serde_json::json!({"codes": [{"code": "SF1234567890124"}]})).len(), 1);
+    assert_eq!(diff_errors(expected.clone(), serde_json::json!({"codes": []})), vec!["$.codes: expected 1 items, got 0".to_string()]);
+    assert_eq!(diff_errors(expected, serde_json::json!({})).len(), 1);
+}
+
+#[test]
+fn diff_accepts_tolerance_overrides() {
+    let expected = serde_json::json!({
+        "tolerance": {"pixels": 5.0},
+        "codes": [{"geometry": {"angle": 0.0, "center": {"x": 172.0, "y": 89.5}}}]
+    });
+    let actual = |x: f64, angle: f64| serde_json::json!({"codes": [{"geometry": {"angle": angle, "center": {"x": x, "y": 89.5}}}]});
+    assert!(diff_errors(expected.clone(), actual(176.0, 0.5)).is_empty());
+    assert_eq!(diff_errors(expected.clone(), actual(178.0, 0.5)).len(), 1);
+    // 未覆盖的角度误差仍为默认的 1 度
+    assert_eq!(diff_errors(expected, actual(172.0, 1.5)).len(), 1);
+}